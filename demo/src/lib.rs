@@ -0,0 +1,6 @@
+//! # Bonaventure Demo
+//! A small demonstration scenario for the Bonaventure framework.
+
+mod scenario;
+
+pub use crate::scenario::Demo;
@@ -0,0 +1,3 @@
+fn main() {
+    bonaventure::run(Box::new(bonaventure_demo::Demo));
+}
@@ -0,0 +1,403 @@
+//! Scenario definition
+
+use bonaventure::types::Dir::*;
+use bonaventure::types::Flag;
+use bonaventure::types::Flag::*;
+use bonaventure::types::ProseBuffer;
+use bonaventure::world_builder::*;
+use bonaventure::world_builder::WBEvent::*;
+use bonaventure::Scenario;
+use bonaventure::World;
+
+// User-defined flags
+const DIRTY: Flag = User("DIRTY");
+const HAS_WATER: Flag = User("HAS_WATER");
+const TAKEN: Flag = User("TAKEN");
+
+/// The demo scenario.
+pub struct Demo;
+
+impl Scenario for Demo {
+    fn title(&self) -> &str {
+        "Bonaventure"
+    }
+
+    fn version(&self) -> &str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn intro(&self) -> &str {
+        "Welcome to Bonaventure!"
+    }
+
+    fn build(&self) -> World {
+        build()
+    }
+}
+
+/// Build the initial state of the game world.
+fn build() -> World {
+    // FIRST, create the world builder
+    let mut wb = WorldBuilder::new();
+
+    // NEXT, configure the player
+    wb.player()
+        .location("clearing")
+        .max_weight(10)
+        .on_examine("You've got all the usual bits.");
+
+    wb.feature("hands", "hands", "hands")
+        .location(PLAYER)
+        .pronoun("them")
+        .flag(DIRTY)
+        .on_examine_hook(&|w,e,buff| {
+            if w.has(e, DIRTY) {
+                buff.puts("You don't remember what you were doing, but it must have been messy.");
+            } else {
+                buff.puts("Fresh and clean.");
+            }
+        })
+        .on_scenery_hook(&|w,e,buff| {
+            if w.has(e, DIRTY) {
+                buff.puts("Your hands are kind of dirty, though.");
+            }
+        });
+
+    // NEXT, create and configure the things in the world.
+
+    // Rule: Story 1
+    wb.rule("rule-story-1")
+        .when(&|w| w.clock() == 0)
+        .print("\
+You don't know where you are.  You don't even know where you want to
+be.  All you know is that your feet are wet, your hands are dirty,
+and gosh, this doesn't look anything like the toy aisle.
+        ");
+
+    // Room: Clearing
+    wb.room("clearing", "A Dreary Clearing")
+        .prose("\
+A wide spot in the woods.  The trees are dense, but there seem to be paths
+heading to the north, south, and east.
+        ")
+        .dead_end(North, "\
+You feel a chill as you approach the edge of the clearing, and after a few more steps are
+overcome with a vague but horrifying sense of deja vu.  You don't remember
+what's back under the trees to the north, but you're pretty sure you didn't like it
+and that you don't want to go find it again.
+        ")
+        .link_both(East, "grotto")
+        .link_both(South, "hilltop");
+
+    // Thing: A ransom note, found in the clearing
+    wb.thing("note", "note", "note")
+        .location("clearing")
+        .size(1)
+        .on_examine_hook(&|w,e,buff| {
+            buff.puts("A note, on plain paper.");
+            if w.has(e, DIRTY) {
+                buff.puts("It looks pretty grubby; someone's been mishandling it.");
+            }
+        })
+        .on_read("\
+If you ever wish to see your toy aisle alive again, put $10,000 dollars
+under the statue in the castle courtyard before nine o'clock tomorrow morning.
+||   -- Your host.
+||Well.  That's a bit alarming.  Where are you going to find $10,000 at this time of day?
+         ");
+
+    // You can't read the note if it's dirty.
+    wb.allow(&ReadThing("note"))
+        .unless(&|w| w.has("note", DIRTY))
+        .print("You've gotten it too dirty to read.");
+
+    // The note gets dirty if the player picks it up with dirty hands.
+    wb.on(&GetThing("note"))
+        .when(&|w| w.has("hands", DIRTY) && !w.has("note", DIRTY))
+        .print("The dirt from your hands got all over the note.")
+        .set_flag("note", DIRTY);
+
+    // Room: Grotto
+    wb.room("grotto", "A Grotto in the Woods")
+        .prose("\
+Nestled in a grotto among the trees you find a pool of water.
+A path leads west.
+        ")
+        .flag(HAS_WATER);
+
+    // Thing: A smooth stone, at the edge of the pool
+    wb.thing("pebble", "smooth stone", "stone")
+        .location("grotto")
+        .nouns(&["pebble"])
+        .adjectives(&["smooth", "flat"])
+        .size(1)
+        .weight(1)
+        .on_examine("A smooth, flat stone, just right for skipping across the pool.");
+
+    // Feature: Pool, a pool in the Grotto
+    wb.feature("pool", "pool", "pool")
+        .location("grotto")
+        .on_examine("\
+Moss grows on the stones around the edge, but the water is clear and
+deep and cold.
+        ");
+
+    // Feature: A hollow log by the pool; things can be put in it.
+    wb.feature("log", "hollow log", "log")
+        .location("grotto")
+        .adjectives(&["hollow"])
+        .container()
+        .max_size(2)
+        .on_scenery("A hollow log lies beside a mossy boulder at the water's edge.")
+        .on_examine("The log is rotten through; there's plenty of room inside.");
+
+    // Feature: A boulder by the pool; things can be put on it.
+    wb.feature("boulder", "boulder", "boulder")
+        .location("grotto")
+        .adjectives(&["mossy"])
+        .supporter()
+        .on_examine("A big, mossy boulder with a flat top.");
+
+    wb.on(&PutIn("pebble", "log"))
+        .print("The stone rattles down into the depths of the log.");
+
+    // Thing: An iron key, hidden in the log.  It unlocks the chest.
+    wb.thing("key", "iron key", "key")
+        .location("log")
+        .adjectives(&["iron"])
+        .size(1)
+        .weight(1)
+        .on_examine("A heavy iron key, flecked with rust.");
+
+    // Room: Hilltop
+    wb.room("hilltop", "A Windy Hilltop")
+        .link(South, "cave-mouth")
+        .prose("\
+The path has led you to the top of a hill, where there is a broad open
+space.  Trails lead to the north and south.
+        ");
+
+    // Thing: The Stone on the Hilltop
+    wb.feature("stone", "marble block", "stone")
+        .location("hilltop")
+        .nouns(&["block", "marble"])
+        .adjectives(&["massive", "marble"])
+        .on_scenery("\
+A massive block of stone squats on the crest of the hill.  There seems to be a sword hilt
+poking out of the top, and there's something on one of the sides.
+        ")
+        .on_examine("\
+It's a massive block of marble, four feet wide and three feet high.  The top is flat, and the
+four sides slope inward.  There's a sword sticking out of the top.  These words are chiseled
+into one side:
+||   * Only The Pure *
+        ");
+
+    // Thing: The Sword in the Stone on the Hilltop
+    wb.thing("sword", "sword", "sword")
+        .location("hilltop")
+        .size(3)
+        .weight(5)
+        .flag(Scenery) // It will appear as part of the stone until removed.
+        .on_examine_hook(&|w,e,buff| {
+            if w.has(e, TAKEN) {
+                buff.puts("\
+The sword, if you want to call it that, is a three-foot length of dark hardwood
+with a sharkskin hilt on one end.  It's polished so that it gleams, and it has no
+sharp edges anywhere.  Carved along the length of it are the words
+\"Emotional Support Sword (TM)\".
+                ");
+            } else {
+                buff.puts("All you can really see is the hilt; the rest is embedded in the stone.");
+            }
+        });
+
+    // If the player tries to pick up the sword with dirty hands, it kills him.
+    wb.allow(&GetThing("sword"))
+        .unless(&|w| w.has("hands", DIRTY))
+        .print("\
+Oh, you so didn't want to touch the sword with dirty hands.
+Weren't you paying attention? Only the pure may touch this sword.
+        ")
+        .kill(PLAYER);
+
+    // When the player takes the sword successfully, magic stuff happens.
+    wb.on(&GetThing("sword"))
+        .once_only()
+        .forget("stone") // Move it to LIMBO
+        .set_flag("sword", TAKEN)
+        .unset_flag("sword", Scenery)
+        .print("\
+The sword almost seems to leap into your hands.  As you marvel at it
+(and, really, there's something odd about it), the marble block dissolves
+into white mist and blows away.
+        ")
+        .award("took the sword", 10);
+
+    // Room: Mouth of Cave
+    wb.room("cave-mouth", "The Mouth of a Forbidding Cave")
+        .link(West, "hilltop")
+        .door(East, "gate")
+        .door(In, "gate")
+        .prose("\
+The trail ends at the mouth of a dark and forbidding cave.  You just
+know that if you go any closer, a stream of bats will fly out and
+scare you silly.  If you choose, you can enter the cave to the east, or
+go back up the trail to the west.
+        ");
+
+    // Feature: An old sea chest by the cave, locked with the iron key.
+    wb.feature("chest", "sea chest", "chest")
+        .location("cave-mouth")
+        .adjectives(&["old", "sea"])
+        .container()
+        .lockable("key")
+        .on_scenery("An old sea chest sits beside the trail, as though someone left it for you.")
+        .on_examine("A sea chest, bound with iron bands, a long way from the sea.");
+
+    // Thing: A gold coin, in the chest.
+    wb.thing("coin", "gold coin", "coin")
+        .location("chest")
+        .adjectives(&["gold"])
+        .size(1)
+        .weight(1)
+        .on_examine("A fat gold coin, stamped with a crown.");
+
+    // The chest won't open for dirty hands.
+    wb.allow(&OpenThing("chest"))
+        .unless(&|w| w.has("hands", DIRTY))
+        .print("The lid won't budge; your hands just slip on it.  Maybe if they weren't so grimy.");
+
+    // Door: An iron gate across the mouth of the cave.
+    wb.door("gate", "iron gate", "gate")
+        .adjectives(&["iron"])
+        .on_scenery_hook(&|w,e,buff| {
+            if w.has(e, Open) {
+                buff.puts("A rusty iron gate stands open across the cave's entrance.");
+            } else {
+                buff.puts("A rusty iron gate is shut across the cave's entrance.");
+            }
+        })
+        .on_examine("The gate's bars are thick with rust, but its hinges look sound.");
+
+    // Thing: A lantern, left at the mouth of the cave.
+    wb.thing("lantern", "brass lantern", "lantern")
+        .location("cave-mouth")
+        .nouns(&["lamp"])
+        .adjectives(&["brass"])
+        .size(2)
+        .weight(4)
+        .light_source()
+        .on_examine_hook(&|w,e,buff| {
+            buff.puts("A battered brass lantern, still half full of oil.");
+            if w.has(e, Lit) {
+                buff.puts("It's burning brightly.");
+            }
+        });
+
+    // Room: The Cave, First Chamber
+    wb.room("cave-1", "In the Cave")
+        .flag(Dark)
+        .door(West, "gate")
+        .door(Out, "gate")
+        .dead_end(East, "\
+At least, it would if the developer had implemented it yet.
+        ")
+        .prose("\
+You're in a damp, muddy cave, its walls glistening in the lantern light.
+The entrance is to the west, and a narrow passage continues to the east.
+        ");
+
+    // The player can't enter the cave without the sword.
+    wb.allow(&EnterRoom("cave-1"))
+        .unless(&|w| !w.owns(PLAYER, "sword"))
+        .print("\
+Oh, hell, no, you're not going in there empty handed.  You'd better go back
+and get that sword.
+        ");
+
+    // The first time the player enters the cave, magic happens.
+    wb.on(&EnterRoom("cave-1"))
+        .once_only()
+        .print("\
+It's an unpleasant place but your sword gives you confidence and warm fuzzies.
+        ");
+
+    // Something lurks in the dark.
+    wb.on(&MoveInDark("cave-1"))
+        .print("Something in the darkness snuffles hungrily as you stumble away.");
+
+    // The player wins by reaching the cave, sword in hand, with a light to see by.
+    // That's all there is, so far.
+    wb.win_when(&|w| w.loc(PLAYER) == "cave-1" && !w.is_dark("cave-1"))
+        .print("\
+You've made it into the cave, armed and ready for anything.  Unfortunately,
+that's as far as this adventure goes, for now.
+        ")
+        .award("reached the cave", 10);
+
+    // If the player dies, the fairy godmother revives him.
+    wb.rule("fairy-godmother-rule")
+        .when(&|w| w.has(PLAYER, Dead))
+        .print("\
+A fairy godmother hovers over your limp body.  She frowns;
+then, apparently against her better judgment, she waves
+her wand.  There's a flash, and she disappears.
+        ")
+        .revive(PLAYER);
+
+    // NEXT, declare achievements awarded by custom commands.
+    wb.achievement("washed your hands", 5);
+
+    // NEXT, add custom commands.
+    // NOTE: Order is important!
+
+    wb.command("wash hands", &|w,_,script| {
+        if !w.has(&w.loc(PLAYER), HAS_WATER) {
+            return Err("That'd be a neat trick, since there's no water here.".into());
+        }
+
+        // TODO: Provide actions that build up paragraphs?
+        let mut buff = ProseBuffer::new();
+        buff.puts("You wash your hands in the water.");
+        if w.has("hands", DIRTY) {
+            buff.puts("They look much cleaner now.");
+        }
+
+        script.print(&buff.get());
+        script.unset_flag("hands", DIRTY);
+        script.award("washed your hands");
+
+        Ok(1)
+    });
+
+    wb.command("wash {visible}", &|_,_,_| {
+        Err("You can't wash that.".into())
+    });
+
+    wb.synonym("wash", "rinse");
+    wb.synonym("wash", "clean");
+
+    // The player must be holding what he skips; if he isn't, he picks it up first.
+    wb.command("skip {taken}", &|w,args,script| {
+        if args[0] != "pebble" {
+            return Err("That wouldn't skip.".into());
+        }
+
+        if !w.has(&w.loc(PLAYER), HAS_WATER) {
+            return Err("There's no water here to skip it on.".into());
+        }
+
+        script.print("\
+The stone skips once, twice, three times across the pool, and then sinks
+out of sight.
+        ");
+        script.forget("pebble");
+
+        Ok(1)
+    });
+
+
+    // NEXT, return the world.
+    wb.world()
+}
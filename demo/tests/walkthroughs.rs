@@ -0,0 +1,178 @@
+//! Golden-transcript regression tests for the scenario.
+//!
+//! Each `tests/walkthroughs/NAME.txt` file is a walkthrough: a list of commands, one per
+//! line, which are fed to a new game one at a time.  The game's output is captured and
+//! compared with the golden transcript in `tests/walkthroughs/NAME.golden`.
+//!
+//! Walkthrough syntax:
+//!
+//! * Blank lines and lines beginning with `#` are ignored, except for
+//! * `#expect` lines, which assert facts about the world after the preceding command:
+//!   * `#expect TAG has FLAG`: the tagged entity has the flag.
+//!   * `#expect OWNER owns THING`: the tagged owner owns the tagged thing.
+//!   * `#expect THING in PLACE`: the tagged thing is located in the tagged place.
+//!   * `#expect clock N`: the clock reads N.
+//!   * `#expect status STATUS`: the game's status is STATUS, e.g., `Won`.
+//!   * Any assertion may be negated by prefixing it with `not`.
+//!
+//! To regenerate the golden transcripts after an intentional change, run the tests with
+//! `BLESS=1` in the environment, and review the differences.
+
+use bonaventure::*;
+use bonaventure_demo::Demo;
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+#[test]
+fn walkthroughs() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/walkthroughs");
+    let bless = env::var_os("BLESS").is_some();
+    let mut failures: Vec<String> = Vec::new();
+
+    let mut scripts: Vec<_> = fs::read_dir(&dir)
+        .expect("Could not read walkthroughs directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty(), "No walkthroughs in {}", dir.display());
+
+    for script in scripts {
+        let name = script.file_stem().unwrap().to_string_lossy().to_string();
+        let golden_file = script.with_extension("golden");
+        let walkthrough = fs::read_to_string(&script).unwrap();
+
+        // FIRST, play the walkthrough, checking its expectations.
+        let transcript = match play(&walkthrough) {
+            Ok(transcript) => transcript,
+            Err(msg) => {
+                failures.push(format!("{}: {}", name, msg));
+                continue;
+            }
+        };
+
+        // NEXT, compare the transcript with the golden transcript.
+        if bless {
+            fs::write(&golden_file, &transcript).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&golden_file) {
+            Ok(golden) => {
+                if let Some(msg) = compare(&golden, &transcript) {
+                    failures.push(format!("{}: {}", name, msg));
+                }
+            }
+            Err(_) => {
+                failures.push(format!("{}: no golden transcript; run with BLESS=1", name));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}
+
+/// Plays the walkthrough, returning the transcript, or an error if an expectation fails.
+fn play(walkthrough: &str) -> Result<String, String> {
+    let output = Rc::new(RefCell::new(MemoryOutput::new()));
+    let mut game = Game::with_output(Box::new(Demo), output.clone());
+    let mut transcript = String::new();
+
+    game.introduce();
+    record(&mut transcript, &output.borrow_mut().take());
+
+    for (i, line) in walkthrough.lines().enumerate() {
+        let line = line.trim();
+
+        if line.starts_with("#expect") {
+            let words: Vec<&str> = line.split_whitespace().skip(1).collect();
+            check(&game, &words)
+                .map_err(|msg| format!("line {}: {}: {}", i + 1, line, msg))?;
+        } else if !line.is_empty() && !line.starts_with('#') {
+            transcript.push_str(&format!("> {}\n", line));
+            game.turn(line);
+            record(&mut transcript, &output.borrow_mut().take());
+        }
+    }
+
+    Ok(transcript)
+}
+
+/// Adds the paragraphs to the transcript, formatted as they would be on the terminal
+/// (but unwrapped).
+fn record(transcript: &mut String, paras: &[(ParaKind, String)]) {
+    for (kind, text) in paras {
+        transcript.push_str(text);
+        transcript.push('\n');
+
+        if *kind != ParaKind::RoomName && *kind != ParaKind::Debug {
+            transcript.push('\n');
+        }
+    }
+}
+
+/// Checks an expectation, returning an error message if it isn't met.
+fn check(game: &Game, words: &[&str]) -> Result<(), String> {
+    let world = game.query();
+    let (negated, words) = match words {
+        ["not", rest @ ..] => (true, rest),
+        _ => (false, words),
+    };
+
+    let met = match words {
+        [tag, "has", flag] => world.has(tag, parse_flag(flag)),
+        [owner, "owns", thing] => world.owns(owner, thing),
+        [thing, "in", place] => world.loc(thing) == *place,
+        ["clock", n] => world.clock().to_string() == *n,
+        ["status", status] => format!("{:?}", game.status()) == *status,
+        _ => return Err("invalid #expect".into()),
+    };
+
+    if met == negated {
+        Err("expectation not met".into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses the name of a flag.  Names other than the engine's own flags are user flags.
+fn parse_flag(name: &str) -> Flag {
+    match name {
+        "FireOnce" => Flag::FireOnce,
+        "Fired" => Flag::Fired,
+        "Dead" => Flag::Dead,
+        "Immovable" => Flag::Immovable,
+        "Scenery" => Flag::Scenery,
+        "Open" => Flag::Open,
+        "Locked" => Flag::Locked,
+        "Dark" => Flag::Dark,
+        "Lit" => Flag::Lit,
+        _ => Flag::User(Box::leak(name.to_string().into_boxed_str())),
+    }
+}
+
+/// Compares the golden transcript with the actual transcript, returning a description
+/// of the first difference, if any.
+fn compare(golden: &str, actual: &str) -> Option<String> {
+    let golden: Vec<&str> = golden.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    for i in 0..golden.len().max(actual.len()) {
+        let expected = golden.get(i).copied();
+        let got = actual.get(i).copied();
+
+        if expected != got {
+            return Some(format!(
+                "transcript differs at line {}:\n  expected: {}\n  actual:   {}",
+                i + 1,
+                expected.unwrap_or("<end of transcript>"),
+                got.unwrap_or("<end of transcript>")
+            ));
+        }
+    }
+
+    None
+}
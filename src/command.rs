@@ -1,8 +1,11 @@
 //! The Command System
 /// This system is for parsing commands and preparing them for execution,
 /// not for executing them.
+use crate::entity::ID;
 use crate::world::World;
+use std::collections::BTreeSet;
 
+#[derive(Clone)]
 pub struct Command {
     // The original text of the command, as entered by the user
     pub input: String,
@@ -25,6 +28,38 @@ impl Command {
     }
 }
 
+/// Splits the player's input into individual commands, which are separated by periods
+/// or by the word "then", e.g., "get note. s then read note".  Empty commands are
+/// ignored.
+pub fn split(input: &str) -> Vec<String> {
+    let mut commands: Vec<String> = Vec::new();
+
+    for sentence in input.split('.') {
+        let mut words: Vec<&str> = Vec::new();
+
+        for word in sentence.split_whitespace() {
+            if word == "then" {
+                push_command(&mut commands, &words);
+                words.clear();
+            } else {
+                words.push(word);
+            }
+        }
+
+        push_command(&mut commands, &words);
+    }
+
+    commands
+}
+
+/// Adds the command consisting of the words to the list, if there are any words.
+fn push_command(commands: &mut Vec<String>, words: &[&str]) {
+    if !words.is_empty() {
+        commands.push(words.join(" "));
+    }
+}
+
+/// Parses a single command.
 pub fn parse(world: &World, input: &str) -> Result<Command, String> {
     // FIRST, remove extraneous characters.
     let input = input.trim();
@@ -33,32 +68,152 @@ pub fn parse(world: &World, input: &str) -> Result<Command, String> {
     for c in input.chars() {
         match c {
             ',' | '!' => {}
-            '.' => {
-                return Err("Input contains '.'; multiple commands not yet support.".into());
-            }
             _ => text.push(c),
         }
     }
 
-    // NEXT, split into words
-    let raw_words: Vec<&str> = text.split_whitespace().collect();
-
-    // NEXT, strip articles and translate synonyms.
-    let mut words: Vec<String> = Vec::new();
-
-    for word in raw_words {
-        match word {
-            "a" | "an" | "the" => (),
-            _ => {
-                if let Some(canon) = world.synonyms.get(word) {
-                    words.push(canon.to_string());
-                } else {
-                    words.push(word.to_string());
-                }
-            }
-        }
-    }
+    // NEXT, split into words, stripping articles.
+    let raw_words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| !["a", "an", "the"].contains(word))
+        .collect();
+
+    // NEXT, translate synonyms.
+    let words: Vec<String> = raw_words.iter().map(|word| canonical(world, word)).collect();
+
+    // NEXT, fold a leading verb phrase into its verb, e.g., "go n" is "go north",
+    // which is "north".
+    let words = fold_phrase(world, words);
 
     // NEXT, return the result.
     Ok(Command::new(input, words))
 }
+
+/// Finds the first word in the command that isn't in the game's vocabulary.  Returns
+/// its index and an error message suggesting known words that are spelled similarly.
+/// Only the words of the things in scope are suggested, so that the suggestions
+/// don't give away things the player can't see.
+pub fn unknown_word(
+    world: &World,
+    words: &[String],
+    scope: &BTreeSet<ID>,
+) -> Option<(usize, String)> {
+    let vocabulary = world.vocabulary();
+    let index = words.iter().position(|word| !vocabulary.contains(word))?;
+    let word = &words[index];
+
+    let mut suggestible = world.command_vocabulary();
+    suggestible.extend(world.thing_words(scope));
+
+    // Allow more typos in longer words.
+    let max = if word.chars().count() <= 4 { 1 } else { 2 };
+
+    // Abbreviations like "n" and "x" are too short to be useful suggestions.
+    let mut near: Vec<(usize, &String)> = suggestible
+        .iter()
+        .filter(|known| known.chars().count() > 2)
+        .map(|known| (edit_distance(word, known), known))
+        .filter(|(distance, _)| *distance <= max)
+        .collect();
+    near.sort();
+
+    let mut msg = format!("I don't know the word '{}'.", word);
+
+    if !near.is_empty() {
+        let quoted: Vec<String> = near.iter().take(3).map(|(_, w)| format!("'{}'", w)).collect();
+        msg.push_str(&format!(" Did you mean {}?", or_list(&quoted)));
+    }
+
+    Some((index, msg))
+}
+
+/// The Levenshtein distance between two words: the number of single-character
+/// insertions, deletions, and substitutions needed to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let next = if ca == *cb {
+                diag
+            } else {
+                1 + diag.min(row[j]).min(row[j + 1])
+            };
+            diag = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Formats a list of items as "A or B" or "A, B, or C".
+pub fn or_list(items: &[String]) -> String {
+    match items {
+        [a] => a.clone(),
+        [a, b] => format!("{} or {}", a, b),
+        [init @ .., last] => format!("{}, or {}", init.join(", "), last),
+        [] => String::new(),
+    }
+}
+
+/// The word's canonical form, i.e., the word it's a synonym for, if any.
+fn canonical(world: &World, word: &str) -> String {
+    match world.synonyms.get(word) {
+        Some(canon) => canon.to_string(),
+        None => word.to_string(),
+    }
+}
+
+/// If the words begin with a verb phrase, e.g., "pick up", replaces the phrase with
+/// its verb.  The words are canonical, so the phrase's words are compared in their
+/// canonical forms.
+fn fold_phrase(world: &World, mut words: Vec<String>) -> Vec<String> {
+    for (phrase, canon) in &world.phrases {
+        if words.len() >= phrase.len()
+            && words.iter().zip(phrase).all(|(w, p)| *w == canonical(world, p))
+        {
+            words.splice(..phrase.len(), std::iter::once(canon.clone()));
+            break;
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_commands() {
+        assert_eq!(split("look"), vec!["look"]);
+        assert_eq!(split("get note. s.read note."), vec!["get note", "s", "read note"]);
+        assert_eq!(split("get note then s then  read note"), vec!["get note", "s", "read note"]);
+        assert_eq!(split("n. then . s"), vec!["n", "s"]);
+        assert!(split(" . ").is_empty());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("sword", "sword"), 0);
+        assert_eq!(edit_distance("swrod", "sword"), 2);
+        assert_eq!(edit_distance("nte", "note"), 1);
+        assert_eq!(edit_distance("", "note"), 4);
+    }
+
+    #[test]
+    fn verb_phrases() {
+        let world = World::new();
+        assert_eq!(parse(&world, "pick up the note").unwrap().words, vec!["get", "note"]);
+        assert_eq!(parse(&world, "look at note").unwrap().words, vec!["examine", "note"]);
+        assert_eq!(parse(&world, "look").unwrap().words, vec!["look"]);
+        assert_eq!(parse(&world, "pick").unwrap().words, vec!["pick"]);
+        assert_eq!(parse(&world, "go n").unwrap().words, vec!["north"]);
+        assert_eq!(parse(&world, "go u").unwrap().words, vec!["up"]);
+    }
+}
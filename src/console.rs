@@ -1,8 +1,10 @@
 //! # Console I/O
-//! Create a Console to read input in "readline" fashion.  Use para() and the para!() macro
-//! to output paragraphs of text.
+//! Create a Console to read input in "readline" fashion.  Game output goes to the
+//! terminal via a TerminalOutput.
 
 use crate::conmark::*;
+use crate::output::Output;
+use crate::output::ParaKind;
 
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
@@ -47,17 +49,19 @@ impl Console {
     }
 }
 
-/// Outputs the text as a block paragraph, i.e., adds an extra newline.
-/// This is the normal way to output text.
-pub fn para(text: &str) {
-    println!("{}\n", conwrap(&confmt(text)));
-}
+/// An Output that writes to the terminal.  Each paragraph is formatted using `conmark`
+/// and wrapped to fit the terminal width, and is followed by a blank line.  Room names
+/// are printed directly above the room's description, and debugging output is printed
+/// as is.
+#[derive(Debug, Default)]
+pub struct TerminalOutput;
 
-/// Formats its arguments using format!(), and outputs them as a wrapped
-/// text block.  Uses `conmark` syntax for line breaks, etc.
-#[macro_export]
-macro_rules! para {
-    ($($arg:tt)*) => (
-        para(&format!($($arg)*));
-    )
+impl Output for TerminalOutput {
+    fn para(&mut self, kind: ParaKind, text: &str) {
+        match kind {
+            ParaKind::RoomName => println!("{}", conwrap(&confmt(text))),
+            ParaKind::Debug => println!("{}", text),
+            _ => println!("{}\n", conwrap(&confmt(text))),
+        }
+    }
 }
@@ -2,6 +2,7 @@
 
 use crate::entity::ID;
 use crate::phys;
+use crate::visual;
 use crate::world::*;
 use crate::types::LinkDest::*;
 
@@ -23,7 +24,7 @@ pub fn dump_world(world: &World) {
 /// List just the given entity
 fn list_entity(world: &World, id: ID) {
     let &tc = world.tags.get(&id).as_ref().unwrap();
-    println!("[{}] {}", tc.id, tc.tag);
+    visual::debug(world, &format!("[{}] {}", tc.id, tc.tag));
 }
 
 /// Dump info about the entity with the given ID
@@ -33,30 +34,38 @@ pub fn dump_entity(world: &World, id: ID) {
     // FIRST, display its location, if any.
     if world.has_location(id) {
         let here = phys::loc(world, id);
-        println!("  Location: [{}] {}", here, world.tag(here));
+        visual::debug(world, &format!("  Location: [{}] {}", here, world.tag(here)));
     }
 
     // FIRST, display the player info
     if world.players.get(&id).is_some() {
-        println!("  Player");
+        visual::debug(world, "  Player");
     }
 
     // NEXT, if it's a thing display the thing info.
     if let Some(thingc) = &world.things.get(&id) {
-        println!("  Thing name: {}", thingc.name);
-        println!("    Noun: {}", thingc.noun);
+        visual::debug(world, &format!("  Thing name: {}", thingc.name));
+        visual::debug(world, &format!("    Nouns: {}", thingc.nouns.join(", ")));
+        if !thingc.adjectives.is_empty() {
+            visual::debug(world, &format!("    Adjectives: {}", thingc.adjectives.join(", ")));
+        }
     }
 
     // NEXT, if it's a room display the room info.
     if let Some(roomc) = &world.rooms.get(&id) {
-        println!("  Room name: {}", roomc.name);
+        visual::debug(world, &format!("  Room name: {}", roomc.name));
         for (dir, dest) in &roomc.links {
             match dest {
                 Room(id) => {
-                    println!("    Link: {:?} to [{}] {}", dir, id, world.tag(*id));
+                    let text = format!("    Link: {:?} to [{}] {}", dir, id, world.tag(*id));
+                    visual::debug(world, &text);
+                },
+                Door(id) => {
+                    let text = format!("    Link: {:?} through door [{}] {}", dir, id, world.tag(*id));
+                    visual::debug(world, &text);
                 },
                 DeadEnd(prose) => {
-                    println!("    Link: {:?} to DeadEnd: {}", dir, prose);
+                    visual::debug(world, &format!("    Link: {:?} to DeadEnd: {}", dir, prose));
                 }
             }
         }
@@ -64,23 +73,23 @@ pub fn dump_entity(world: &World, id: ID) {
 
     // NEXT, if it's a rule display its actions.
     if let Some(rulec) = &world.rules.get(&id) {
-        rulec.script.dump("  ");
+        rulec.script.dump(world, "  ");
     }
 
     // NEXT, display its flags, if any.
     if let Some(flagc) = &world.flag_sets.get(&id) {
         for flag in flagc.iter() {
-            println!("  Flag: {:?}", flag);
+            visual::debug(world, &format!("  Flag: {:?}", flag));
         }
     }
 
     // NEXT, display its inventory, if any.
     if let Some(invc) = world.inventories.get(&id) {
         if invc.things.is_empty() {
-            println!("  Contains: nothing");
+            visual::debug(world, "  Contains: nothing");
         } else {
             for tid in &invc.things {
-                println!("  Contains: [{}] {}", tid, world.tag(*tid));
+                visual::debug(world, &format!("  Contains: [{}] {}", tid, world.tag(*tid)));
             }
         }
     }
@@ -88,7 +97,8 @@ pub fn dump_entity(world: &World, id: ID) {
     // NEXT, display any associated prose (given the entity's current state)
     if let Some(prosec) = &world.proses.get(&id) {
         for (prose_type, prose) in &prosec.types {
-            println!("  Prose [{:?}]: {}", prose_type, prose.as_string(world, id));
+            let text = prose.as_string(world, id);
+            visual::debug(world, &format!("  Prose [{:?}]: {}", prose_type, text));
         }
     }
 }
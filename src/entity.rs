@@ -1,5 +1,6 @@
 //! The Entity Data Type and Builder
 
+pub mod door_component;
 pub mod flag_set_component;
 pub mod inventory_component;
 pub mod location_component;
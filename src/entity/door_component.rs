@@ -0,0 +1,34 @@
+//! The Door Component
+
+use crate::entity::ID;
+use std::collections::BTreeSet;
+
+/// Information specific to doors: things that stand between two rooms, and that
+/// both rooms link to.
+#[derive(Debug, Clone, Default)]
+pub struct DoorComponent {
+    /// The two rooms the door connects.
+    pub rooms: BTreeSet<ID>,
+}
+
+impl DoorComponent {
+    /// Create a new door component.  The rooms are added as they link to it.
+    pub fn new() -> Self {
+        Self {
+            rooms: BTreeSet::new(),
+        }
+    }
+
+    /// The room on the other side of the door from the given room.
+    ///
+    /// * Panics if the door doesn't connect the room to another room.
+    pub fn other_side(&self, room: ID) -> ID {
+        assert!(self.rooms.contains(&room), "Door doesn't connect room: [{}]", room);
+
+        *self
+            .rooms
+            .iter()
+            .find(|id| **id != room)
+            .unwrap_or_else(|| panic!("Door leads nowhere from room: [{}]", room))
+    }
+}
@@ -9,6 +9,13 @@ pub struct InventoryComponent {
     /// A set of things in the inventory.  We use a BTreeSet so that we preserve the order
     /// in which things were added.
     pub things: BTreeSet<ID>,
+
+    /// The total size of the things the inventory can hold, if it's limited.
+    pub max_size: Option<usize>,
+
+    /// The total weight of the things the inventory can hold, including whatever
+    /// is in or on them, if it's limited.
+    pub max_weight: Option<usize>,
 }
 
 impl InventoryComponent {
@@ -16,6 +23,8 @@ impl InventoryComponent {
     pub fn new() -> Self {
         Self {
             things: BTreeSet::new(),
+            max_size: None,
+            max_weight: None,
         }
     }
 
@@ -3,18 +3,31 @@
 //! and other ThingComponent attributes do not.
 
 use crate::entity::ID;
+use crate::types::Placement;
 use crate::world::LIMBO;
 
 /// Inventories of Things
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct LocationComponent {
     /// The location of this entity, for entities that can have a location.
     pub id: ID,
+
+    /// Whether the entity is in its location or on top of it.
+    pub placement: Placement,
 }
 
 impl LocationComponent {
     /// Create a new component
     pub fn new() -> Self {
-        Self { id: LIMBO }
+        Self {
+            id: LIMBO,
+            placement: Placement::In,
+        }
+    }
+}
+
+impl Default for LocationComponent {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,13 +1,31 @@
 //! Thing Data
 
+use crate::entity::ID;
+
 /// Information specific to things.
 #[derive(Debug, Clone)]
 pub struct ThingComponent {
     /// The thing's name, for display in inventory lists.
     pub name: String,
 
-    /// The thing's noun, for use in commands
-    pub noun: String,
+    /// The thing's nouns, for use in commands.  The first is the thing's primary noun.
+    pub nouns: Vec<String>,
+
+    /// The thing's adjectives, for use in commands to tell it apart from other things
+    /// with the same nouns.
+    pub adjectives: Vec<String>,
+
+    /// The pronoun by which the player can refer to the thing, e.g., "it" or "them".
+    pub pronoun: String,
+
+    /// The key that locks and unlocks the thing, if it's lockable.
+    pub key: Option<ID>,
+
+    /// The thing's size, for filling inventories with limited room.
+    pub size: usize,
+
+    /// The thing's weight, not counting whatever is in or on it.
+    pub weight: usize,
 }
 
 impl ThingComponent {
@@ -15,7 +33,59 @@ impl ThingComponent {
     pub fn new(name: &str, noun: &str) -> ThingComponent {
         ThingComponent {
             name: name.into(),
-            noun: noun.into(),
+            nouns: vec![noun.into()],
+            adjectives: Vec::new(),
+            pronoun: "it".into(),
+            key: None,
+            size: 0,
+            weight: 0,
+        }
+    }
+
+    /// The thing's primary noun.
+    pub fn noun(&self) -> &str {
+        &self.nouns[0]
+    }
+
+    /// Is the word one of the thing's nouns or adjectives?
+    pub fn has_word(&self, word: &str) -> bool {
+        self.nouns.iter().any(|n| n == word) || self.adjectives.iter().any(|a| a == word)
+    }
+
+    /// Scores how well the noun phrase matches the thing: two points for each noun and
+    /// one for each adjective.  The score is zero if the phrase is empty or contains
+    /// any other word.
+    pub fn score(&self, phrase: &[&str]) -> usize {
+        let mut score = 0;
+
+        for word in phrase {
+            if self.nouns.iter().any(|n| n == word) {
+                score += 2;
+            } else if self.adjectives.iter().any(|a| a == word) {
+                score += 1;
+            } else {
+                return 0;
+            }
         }
+
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score() {
+        let mut stone = ThingComponent::new("smooth stone", "stone");
+        stone.nouns.push("pebble".into());
+        stone.adjectives.push("smooth".into());
+
+        assert_eq!(stone.score(&["stone"]), 2);
+        assert_eq!(stone.score(&["smooth", "pebble"]), 3);
+        assert_eq!(stone.score(&["smooth"]), 1);
+        assert_eq!(stone.score(&["red", "stone"]), 0);
+        assert_eq!(stone.score(&[]), 0);
     }
 }
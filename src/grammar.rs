@@ -0,0 +1,216 @@
+//! # Command Grammar
+//! Commands are declared as patterns of tokens: literal words, and typed slots written
+//! in braces, e.g., `put {held} in {container}`.  A pattern must begin with a literal
+//! word, its verb.  A thing slot matches a noun phrase of one or more words, which the
+//! player control system resolves to a thing of the slot's type; a `{word}` slot matches
+//! any single word.
+//!
+//! This module is concerned only with the syntax of patterns; see `player_control` for
+//! how matched commands are resolved and executed.
+
+use crate::entity::ID;
+
+/// The pronouns the player can use in place of a noun phrase, to refer to the thing
+/// most recently mentioned that takes that pronoun.
+pub const PRONOUNS: [&str; 4] = ["it", "them", "him", "her"];
+
+/// Is the word a pronoun?
+pub fn is_pronoun(word: &str) -> bool {
+    PRONOUNS.contains(&word)
+}
+
+/// The types of slot a pattern can contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    /// `{visible}`: A thing the player can see.
+    Visible,
+
+    /// `{held}`: A thing the player is carrying.
+    Held,
+
+    /// `{here}`: A thing in the player's location, other than the player.
+    Here,
+
+    /// `{taken}`: A thing the player can see, which he must be holding.  If he isn't,
+    /// he takes it first, unless it's immovable.
+    Taken,
+
+    /// `{container}`: A container the player can see.
+    Container,
+
+    /// `{supporter}`: A supporter the player can see.
+    Supporter,
+
+    /// `{agent}`: An agent, i.e., a character, the player can see.
+    Agent,
+
+    /// `{word}`: Any single word, e.g., the name of a save file.
+    Word,
+}
+
+/// A token in a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// A literal word
+    Word(String),
+
+    /// A slot, to be filled by the player's input
+    Slot(Slot),
+}
+
+/// The value of a slot, once resolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Arg {
+    /// The thing to which a thing slot's noun phrase resolved.
+    Thing(ID),
+
+    /// The word that filled a `{word}` slot.
+    Word(String),
+}
+
+/// A command pattern.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    /// Parses a pattern from its text, e.g., `put {held} in {container}`.
+    ///
+    /// * Panics if the pattern doesn't begin with a literal word, contains an unknown
+    ///   slot type, or contains two adjacent slots.
+    pub fn parse(text: &str) -> Self {
+        let mut tokens: Vec<Token> = Vec::new();
+
+        for word in text.split_whitespace() {
+            let token = match word {
+                "{visible}" => Token::Slot(Slot::Visible),
+                "{held}" => Token::Slot(Slot::Held),
+                "{here}" => Token::Slot(Slot::Here),
+                "{taken}" => Token::Slot(Slot::Taken),
+                "{container}" => Token::Slot(Slot::Container),
+                "{supporter}" => Token::Slot(Slot::Supporter),
+                "{agent}" => Token::Slot(Slot::Agent),
+                "{word}" => Token::Slot(Slot::Word),
+                _ if word.starts_with('{') => panic!("Unknown slot in pattern: {}", text),
+                _ => Token::Word(word.into()),
+            };
+
+            if let (Some(Token::Slot(_)), Token::Slot(_)) = (tokens.last(), &token) {
+                panic!("Adjacent slots in pattern: {}", text);
+            }
+
+            tokens.push(token);
+        }
+
+        assert!(
+            matches!(tokens.first(), Some(Token::Word(_))),
+            "Pattern must begin with a verb: {}",
+            text
+        );
+
+        Self { tokens }
+    }
+
+    /// The pattern's verb, i.e., its first word.
+    pub fn verb(&self) -> &str {
+        match &self.tokens[0] {
+            Token::Word(word) => word,
+            Token::Slot(_) => unreachable!(),
+        }
+    }
+
+    /// The pattern's literal words, including the verb.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Word(word) => Some(word.as_str()),
+            Token::Slot(_) => None,
+        })
+    }
+
+    /// The pattern's slots, in order.
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Word(_) => None,
+            Token::Slot(slot) => Some(*slot),
+        })
+    }
+
+    /// Matches the command's words against the pattern.  On success, returns each
+    /// slot with the words that filled it.
+    pub fn matches<'a, 'b>(&self, words: &'a [&'b str]) -> Option<Vec<(Slot, &'a [&'b str])>> {
+        let mut slots = Vec::new();
+
+        if match_tokens(&self.tokens, words, &mut slots) {
+            Some(slots)
+        } else {
+            None
+        }
+    }
+}
+
+/// Matches the words against the tokens, accumulating the slots and their words.
+/// Thing slots match as many words as necessary for the rest of the pattern to match.
+fn match_tokens<'a, 'b>(
+    tokens: &[Token],
+    words: &'a [&'b str],
+    slots: &mut Vec<(Slot, &'a [&'b str])>,
+) -> bool {
+    let (token, rest) = match tokens.split_first() {
+        Some(split) => split,
+        None => return words.is_empty(),
+    };
+
+    match token {
+        Token::Word(word) => {
+            !words.is_empty() && words[0] == word && match_tokens(rest, &words[1..], slots)
+        }
+        Token::Slot(slot) => {
+            let max = if *slot == Slot::Word { 1 } else { words.len() };
+
+            for n in 1..=max.min(words.len()) {
+                slots.push((*slot, &words[..n]));
+                if match_tokens(rest, &words[n..], slots) {
+                    return true;
+                }
+                slots.pop();
+            }
+
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let pattern = Pattern::parse("put {held} in {visible}");
+        assert_eq!(pattern.verb(), "put");
+        assert_eq!(pattern.words().collect::<Vec<_>>(), vec!["put", "in"]);
+        assert_eq!(pattern.slots().collect::<Vec<_>>(), vec![Slot::Held, Slot::Visible]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_adjacent_slots() {
+        Pattern::parse("give {held} {visible}");
+    }
+
+    #[test]
+    fn matches() {
+        let pattern = Pattern::parse("put {held} in {visible}");
+        let slots = pattern.matches(&["put", "red", "stone", "in", "box"]).unwrap();
+        assert_eq!(slots[0], (Slot::Held, &["red", "stone"][..]));
+        assert_eq!(slots[1], (Slot::Visible, &["box"][..]));
+
+        assert!(pattern.matches(&["put", "stone"]).is_none());
+        assert!(pattern.matches(&["put", "in", "box"]).is_none());
+
+        let pattern = Pattern::parse("save {word}");
+        assert!(pattern.matches(&["save", "game"]).is_some());
+        assert!(pattern.matches(&["save", "my", "game"]).is_none());
+    }
+}
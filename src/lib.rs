@@ -1,111 +1,400 @@
 //! # Bonaventure: A Text Adventure Framework
-/// Bonaventure is a simple text adventure framework.  At present, it is used to
-/// implement a single game; see src/scenario.rs.  Eventually it might support
-/// multiple games.
+/// Bonaventure is a simple text adventure framework.  A game is defined by a
+/// Scenario, which uses the WorldBuilder API to build the game world; see the
+/// demo crate for an example.
 mod command;
 mod conmark;
-#[macro_use]
 mod console;
 mod debug;
 mod entity;
+mod grammar;
+mod output;
 mod phys;
 mod player_control;
 mod rule;
+mod save;
 mod scenario;
 mod script;
-mod types;
+mod transcript;
+pub mod types;
 mod visual;
 mod world;
-#[allow(dead_code)] // Games won't use all features.
-mod world_builder;
+pub mod world_builder;
 
+use crate::command::Command;
+use crate::console::TerminalOutput;
+use crate::transcript::Transcript;
 use crate::types::Event;
-use crate::world::*;
+use crate::types::Time;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub use crate::output::MemoryOutput;
+pub use crate::output::Output;
+pub use crate::output::OutputHandle;
+pub use crate::output::ParaKind;
+pub use crate::scenario::Scenario;
+pub use crate::script::Script;
+pub use crate::types::Flag;
+pub use crate::types::GameStatus;
+pub use crate::world::World;
+pub use crate::world::WorldQuery;
 
 /// The main game object.  It owns the world as it currently is, and supports restart
 /// and undo, etc.
 /// TODO: Possibly, this should live elsewhere.
 pub struct Game {
+    // The scenario being played
+    scenario: Box<dyn Scenario>,
+
     // THe current world
     world: World,
 
-    // Undo information
-    undo_info: Option<World>,
+    // Where the game's output goes; shared by every world the game creates.  It's
+    // the transcript, which passes the output on to the real output.
+    output: OutputHandle,
+
+    // The transcript, which can record the game to a file.
+    transcript: Rc<RefCell<Transcript>>,
+
+    // Undo information: the world as it was before each undoable command, most
+    // recent last.
+    undo_stack: Vec<HistoryEntry>,
+
+    // Redo information: the world as it was before each undo, most recent last.
+    redo_stack: Vec<HistoryEntry>,
+
+    // The last command that changed the world, for "again".  It's kept outside the
+    // world so that undo doesn't affect it.
+    last_command: Option<Command>,
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Self::new()
-    }
+/// The maximum number of commands that can be undone.
+const UNDO_LIMIT: usize = 50;
+
+/// A saved world state, for undo and redo.
+struct HistoryEntry {
+    // The command that changed the world from this state, as entered by the player.
+    input: String,
+
+    // The world state.
+    world: World,
 }
 
 impl Game {
-    /// Create the game object
-    pub fn new() -> Game {
+    /// Create the game object for the scenario, writing output to the terminal.
+    pub fn new(scenario: Box<dyn Scenario>) -> Game {
+        Self::with_output(scenario, Rc::new(RefCell::new(TerminalOutput)))
+    }
+
+    /// Create the game object for the scenario, writing output to the given output.
+    pub fn with_output(scenario: Box<dyn Scenario>, output: OutputHandle) -> Game {
+        let transcript = Rc::new(RefCell::new(Transcript::new(output)));
+        let output: OutputHandle = transcript.clone();
+
         Game {
-            world: scenario::build(),
-            undo_info: None,
+            world: Self::build(scenario.as_ref(), &output),
+            scenario,
+            output,
+            transcript,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_command: None,
         }
     }
 
+    /// Builds a fresh copy of the scenario's world, attached to the given output.
+    fn build(scenario: &dyn Scenario, output: &OutputHandle) -> World {
+        let mut world = scenario.build();
+        world.output = output.clone();
+        world
+    }
+
+    /// The scenario being played.
+    pub fn scenario(&self) -> &dyn Scenario {
+        self.scenario.as_ref()
+    }
+
+    /// A query interface to the current state of the world.
+    pub fn query(&self) -> &dyn WorldQuery {
+        &self.world
+    }
+
     /// Introduce the game: print a welcome message, and visualize the initial location
     pub fn introduce(&mut self) {
-        println!("Welcome to Bonaventure!\n");
+        visual::info(&self.world, self.scenario.intro());
 
         // The first turn is always an implicit "look at the current setting".
         // This will also give everything else a chance to move.
         self.turn("look");
+
+        // ...but it isn't the player's command to repeat or undo.
+        self.last_command = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// The status of the game: whether it's still being played, and if not, how it ended.
+    pub fn status(&self) -> GameStatus {
+        self.world.status
     }
 
-    /// Execute one game turn.
-    pub fn turn(&mut self, cmd: &str) {
-        // FIRST, let the player do what he does.
+    /// Execute one game turn, returning the game's status at the end of it.
+    pub fn turn(&mut self, cmd: &str) -> GameStatus {
+        self.transcript.borrow_mut().input(cmd);
+
+        // FIRST, if the game is over the player may only restart, restore, undo, or quit.
+        if self.is_over() {
+            player_control::game_over(self, cmd);
+            return self.status();
+        }
+
+        // NEXT, let the player do what he does.
         player_control::system(self, &cmd);
 
-        // NEXT, handle rules
-        rule::fire_event(&mut self.world, &Event::Turn);
+        // NEXT, if the game has just ended, say so.
+        if self.is_over() {
+            self.announce_ending();
+        }
 
-        // NEXT, Increment the clock
-        // TODO: Probably don't want to do this here.  Some commands should
-        // take time, and some shouldn't.  This should probably be in the
-        // player_control system.
-        self.world.clock += 1;
+        self.status()
+    }
+
+    /// Lets the given number of turns pass after a command: for each turn, handle
+    /// rules and increment the clock.  Time stops when the game ends.
+    fn pass_time(&mut self, time: Time) {
+        for _ in 0..time {
+            rule::fire_event(&mut self.world, &Event::Turn);
+            self.world.clock += 1;
+
+            if self.status() != GameStatus::Playing {
+                break;
+            }
+        }
+
+        // The player loses if he's dead and no rule has revived him.
+        if self.status() == GameStatus::Playing && self.world.has_flag(self.world.pid, Flag::Dead) {
+            self.world.status = GameStatus::Lost;
+        }
+    }
+
+    /// Has the game been won or lost?  The player can still restart, restore, or undo.
+    fn is_over(&self) -> bool {
+        match self.status() {
+            GameStatus::Won | GameStatus::Lost => true,
+            GameStatus::Playing | GameStatus::Quit => false,
+        }
+    }
+
+    /// Announces the end of the game, and asks the player what to do next.
+    fn announce_ending(&self) {
+        // A dead player has already been told so.
+        if self.status() == GameStatus::Won {
+            visual::act(&self.world, "*** You have won! ***");
+        } else if !self.world.has_flag(self.world.pid, Flag::Dead) {
+            visual::act(&self.world, "*** You have lost. ***");
+        }
+
+        if !self.world.achievements.is_empty() {
+            visual::info(
+                &self.world,
+                &format!(
+                    "You scored {} of {} points in {} turns.",
+                    self.world.score, self.world.max_score, self.world.clock
+                ),
+            );
+        }
+
+        visual::info(&self.world, &player_control::game_over_prompt(&self.world));
     }
 
     /// Restart the game: recreate the initial scenario.
     pub fn restart(&mut self) {
-        self.world = scenario::build();
-        self.undo_info = None;
+        self.world = Self::build(self.scenario.as_ref(), &self.output);
+        self.undo_stack.clear();
+        self.redo_stack.clear();
         self.introduce();
     }
 
-    /// Saves the world state for later undo.
-    pub fn save_for_undo(&mut self, undo_info: World) {
-        // At present, we save only one turn.
-        self.undo_info = Some(undo_info);
+    /// Saves the game's current state to the named file.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        save::save(&self.world, self.scenario.as_ref(), path)
+    }
+
+    /// Restores the game from the named file.  The saved state is applied to a freshly
+    /// built scenario; if the file can't be restored, the current game is unchanged.
+    pub fn restore(&mut self, path: &str) -> Result<(), String> {
+        let mut world = Self::build(self.scenario.as_ref(), &self.output);
+        save::restore(&mut world, self.scenario.as_ref(), path)?;
+        self.world = world;
+        Ok(())
+    }
+
+    /// Starts recording a transcript of the game to the named file.
+    pub fn start_transcript(&mut self, path: &str) -> Result<(), String> {
+        self.transcript.borrow_mut().start(path, self.scenario.as_ref())
+    }
+
+    /// Stops recording the transcript.
+    pub fn stop_transcript(&mut self) {
+        self.transcript.borrow_mut().stop();
+    }
+
+    /// Is a transcript being recorded?
+    pub fn is_recording(&self) -> bool {
+        self.transcript.borrow().is_recording()
+    }
+
+    /// Saves the world state as it was before the given input, for later undo.
+    /// Any previously undone commands can no longer be redone.
+    pub fn save_for_undo(&mut self, input: &str, undo_info: World) {
+        if !self.world.allow_undo {
+            return;
+        }
+
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+
+        self.undo_stack.push(HistoryEntry {
+            input: input.into(),
+            world: undo_info,
+        });
+        self.redo_stack.clear();
     }
 
     /// Is there any undo info?
     pub fn has_undo(&self) -> bool {
-        self.undo_info.is_some()
+        !self.undo_stack.is_empty()
+    }
+
+    /// Is there any redo info?
+    pub fn has_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Undoes the most recent command, returning its input.
+    pub fn undo(&mut self) -> String {
+        let entry = self.undo_stack.pop().expect("Cannot undo; no undo info");
+        let world = std::mem::replace(&mut self.world, entry.world);
+        self.redo_stack.push(HistoryEntry {
+            input: entry.input.clone(),
+            world,
+        });
+        entry.input
     }
 
-    pub fn undo(&mut self) {
-        assert!(self.has_undo(), "Cannot undo; no undo info");
-        self.world = self.undo_info.take().unwrap();
+    /// Redoes the most recently undone command, returning its input.
+    pub fn redo(&mut self) -> String {
+        let entry = self.redo_stack.pop().expect("Cannot redo; no redo info");
+        let world = std::mem::replace(&mut self.world, entry.world);
+        self.undo_stack.push(HistoryEntry {
+            input: entry.input.clone(),
+            world,
+        });
+        entry.input
     }
 }
 
-/// Runs the program.
-pub fn run() {
+/// Runs the scenario as an interactive game on the terminal.
+pub fn run(scenario: Box<dyn Scenario>) {
     // FIRST, create the game world.
-    let mut game = Game::new();
+    let mut game = Game::new(scenario);
     game.introduce();
 
     // NEXT, enter the game loop.
     let mut con = console::Console::new();
 
-    loop {
-        game.turn(&con.readline("> "));
+    while game.turn(&con.readline("> ")) != GameStatus::Quit {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_builder::WorldBuilder;
+
+    const ASLEEP: Flag = Flag::User("ASLEEP");
+
+    struct TestScenario;
+
+    impl Scenario for TestScenario {
+        fn title(&self) -> &str {
+            "Game Test"
+        }
+
+        fn version(&self) -> &str {
+            "1.0"
+        }
+
+        fn intro(&self) -> &str {
+            ""
+        }
+
+        fn build(&self) -> World {
+            let mut wb = WorldBuilder::new();
+            wb.player().location("room");
+            wb.room("room", "Room").prose("A plain room.");
+            wb.thing("fish", "fish", "fish").location("PLAYER");
+            wb.thing("rock", "rock", "rock").location("room");
+            wb.thing("heron", "heron", "heron").location("room").agent();
+            wb.thing("desk", "desk", "desk").location("room").container().supporter();
+            wb.thing("pen", "pen", "pen").location("desk");
+            wb.thing("lamp", "lamp", "lamp").location_on("desk");
+            wb.command("nap", &|_, _, script| {
+                script.set_flag("PLAYER", ASLEEP);
+                Ok(3)
+            });
+            wb.command("give {held} to {agent}", &|_, args, script| {
+                script.print(&format!("The {} takes the {}.", args[1], args[0]));
+                script.forget(args[0]);
+                Ok(1)
+            });
+            wb.win_when(&|w| w.has("PLAYER", ASLEEP));
+            wb.world()
+        }
+    }
+
+    fn new_game() -> (Game, Rc<RefCell<MemoryOutput>>) {
+        let output = Rc::new(RefCell::new(MemoryOutput::new()));
+        let mut game = Game::with_output(Box::new(TestScenario), output.clone());
+        game.introduce();
+        output.borrow_mut().take();
+        (game, output)
+    }
+
+    fn last_para(output: &Rc<RefCell<MemoryOutput>>) -> String {
+        output.borrow_mut().take().pop().expect("no output").1
+    }
+
+    #[test]
+    fn game_ends_mid_command() {
+        let (mut game, _) = new_game();
+        assert_eq!(game.world.clock, 1);
+
+        // The nap would take three turns, but the game is won after the first.
+        assert_eq!(game.turn("nap"), GameStatus::Won);
+        assert_eq!(game.world.clock, 2);
+    }
+
+    #[test]
+    fn container_supporter() {
+        let (mut game, output) = new_game();
+
+        game.turn("look");
+        let paras: Vec<String> = output.borrow_mut().take().into_iter().map(|p| p.1).collect();
+        assert!(paras.contains(&"In the desk: pen.".to_string()));
+        assert!(paras.contains(&"On the desk: lamp.".to_string()));
+    }
+
+    #[test]
+    fn agent_slot() {
+        let (mut game, output) = new_game();
+
+        game.turn("give fish to rock");
+        assert_eq!(last_para(&output), "You can only do that to someone.");
+
+        game.turn("give fish to heron");
+        assert_eq!(last_para(&output), "The heron takes the fish.");
+        assert!(!game.world.owns("PLAYER", "fish"));
     }
 }
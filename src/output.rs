@@ -0,0 +1,110 @@
+//! # Game Output
+//! All game output goes through an Output, which receives paragraphs of `conmark` text
+//! tagged with their kind.  The game normally writes to the terminal (see
+//! `console::TerminalOutput`); a MemoryOutput captures the output instead, e.g., for
+//! embedding the engine or testing it.
+
+use crate::conmark::confmt;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The kinds of paragraph the game outputs.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum ParaKind {
+    /// The result of a player action, e.g., "Taken."
+    Act,
+
+    /// An error message, e.g., "You don't see any such thing."
+    Error,
+
+    /// Information, e.g., help text or rule output.
+    Info,
+
+    /// A room's name, as the heading of its description.
+    RoomName,
+
+    /// A room's descriptive prose.
+    RoomBody,
+
+    /// A room's name, alone, as the brief description of a room the player has
+    /// already seen.
+    RoomBrief,
+
+    /// The list of things visible in a room.
+    RoomContents,
+
+    /// A thing's (or the player's) descriptive prose.
+    Thing,
+
+    /// The player's inventory.
+    Inventory,
+
+    /// A line of debugging output.  Debugging output is preformatted, and isn't
+    /// subject to `conmark` formatting.
+    Debug,
+}
+
+/// A destination for game output.
+pub trait Output {
+    /// Outputs a paragraph of the given kind.  The text uses `conmark` syntax,
+    /// except for Debug paragraphs.
+    fn para(&mut self, kind: ParaKind, text: &str);
+}
+
+/// A shared handle to an Output.  Every snapshot of the World shares the same
+/// output.
+pub type OutputHandle = Rc<RefCell<dyn Output>>;
+
+/// An Output that records paragraphs in memory.  The text of each paragraph is
+/// formatted using `conmark`, but isn't wrapped.
+#[derive(Debug, Default)]
+pub struct MemoryOutput {
+    paras: Vec<(ParaKind, String)>,
+}
+
+impl MemoryOutput {
+    /// Creates an empty MemoryOutput.
+    pub fn new() -> Self {
+        Self { paras: Vec::new() }
+    }
+
+    /// The paragraphs recorded so far.
+    pub fn paras(&self) -> &[(ParaKind, String)] {
+        &self.paras
+    }
+
+    /// Returns the paragraphs recorded so far, and clears the record.
+    pub fn take(&mut self) -> Vec<(ParaKind, String)> {
+        std::mem::take(&mut self.paras)
+    }
+}
+
+impl Output for MemoryOutput {
+    fn para(&mut self, kind: ParaKind, text: &str) {
+        let text = match kind {
+            ParaKind::Debug => text.to_string(),
+            _ => confmt(text),
+        };
+
+        self.paras.push((kind, text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_output() {
+        let mut output = MemoryOutput::new();
+        output.para(ParaKind::Act, "  Taken.  ");
+        output.para(ParaKind::Debug, "  Flag: Dead");
+
+        assert_eq!(output.paras().len(), 2);
+        assert_eq!(output.paras()[0], (ParaKind::Act, "Taken.".to_string()));
+
+        let paras = output.take();
+        assert_eq!(paras[1], (ParaKind::Debug, "  Flag: Dead".to_string()));
+        assert!(output.paras().is_empty());
+    }
+}
@@ -7,9 +7,12 @@
 use crate::entity::ID;
 use crate::rule;
 use crate::types::Dir;
+use crate::types::Event;
 use crate::types::Event::*;
 use crate::types::LinkDest;
+use crate::types::Flag;
 use crate::types::Flag::*;
+use crate::types::Placement;
 use crate::visual;
 use crate::world::World;
 use crate::world::LIMBO;
@@ -29,6 +32,142 @@ pub fn loc(world: &World, thing: ID) -> ID {
     world.locations[&thing].id
 }
 
+/// Returns whether the entity is in its location or on it.
+///
+/// * Panics if the entity isn't the sort of thing that has a location.
+pub fn placement(world: &World, thing: ID) -> Placement {
+    assert_has_location(world, thing);
+
+    world.locations[&thing].placement
+}
+
+/// Determines whether the outer entity encloses the inner entity, i.e., whether
+/// the inner entity is in or on the outer, directly or indirectly.
+pub fn encloses(world: &World, outer: ID, inner: ID) -> bool {
+    let mut id = inner;
+
+    while let Some(locc) = world.locations.get(&id) {
+        if locc.id == outer {
+            return true;
+        }
+        if locc.id == LIMBO || locc.id == id {
+            return false;
+        }
+        id = locc.id;
+    }
+
+    false
+}
+
+/// Is the thing openable, and closed?
+pub fn is_closed(world: &World, thing: ID) -> bool {
+    world.has_flag(thing, Openable) && !world.has_flag(thing, Open)
+}
+
+/// Returns the total size of the things in the owner's inventory.  Things in or on
+/// those things don't count; they take up room only in their own containers.
+pub fn load_size(world: &World, owner: ID) -> usize {
+    contents(world, owner).iter().map(|id| size(world, *id)).sum()
+}
+
+/// Returns the total weight of the things in the owner's inventory, including
+/// whatever is in or on them.
+pub fn load_weight(world: &World, owner: ID) -> usize {
+    contents(world, owner).iter().map(|id| weight(world, *id)).sum()
+}
+
+/// Returns the thing's size.
+fn size(world: &World, thing: ID) -> usize {
+    world.things.get(&thing).map_or(0, |thingc| thingc.size)
+}
+
+/// Returns the thing's weight, including whatever is in or on it.
+fn weight(world: &World, thing: ID) -> usize {
+    let own = world.things.get(&thing).map_or(0, |thingc| thingc.weight);
+
+    if world.has_inventory(thing) {
+        own + load_weight(world, thing)
+    } else {
+        own
+    }
+}
+
+/// Checks whether the thing can be moved in or on the destination without
+/// overflowing its limits on size and weight, or the weight limits of whatever is
+/// carrying it.  Returns a message explaining why not if it can't.
+pub fn check_capacity(world: &World, thing: ID, dest: ID, placement: Placement) -> PhysResult {
+    let name = &world.things[&thing].name;
+    let invc = &world.inventories[&dest];
+
+    // FIRST, check the destination's room.
+    if let Some(max) = invc.max_size {
+        if load_size(world, dest) + size(world, thing) > max {
+            return Err(if world.is_player(dest) {
+                format!("You're carrying too much to take the {}.", name)
+            } else {
+                let prep = preposition(placement);
+                format!("The {} won't fit {} the {}.", name, prep, world.things[&dest].name)
+            });
+        }
+    }
+
+    // NEXT, check the weight on the destination, and on anything carrying it that
+    // isn't carrying the thing already.
+    let mut id = dest;
+
+    while world.has_inventory(id) && !encloses(world, id, thing) {
+        if let Some(max) = world.inventories[&id].max_weight {
+            if load_weight(world, id) + weight(world, thing) > max {
+                return Err(if !world.is_player(id) {
+                    format!("The {} can't bear that much weight.", world.things[&id].name)
+                } else if weight(world, thing) > max {
+                    format!("The {} is too heavy for you.", name)
+                } else {
+                    format!("You're carrying too much to take the {}.", name)
+                });
+            }
+        }
+
+        if !world.has_location(id) || loc(world, id) == LIMBO {
+            break;
+        }
+        id = loc(world, id);
+    }
+
+    Ok(())
+}
+
+/// The preposition for things with the given placement: "in" or "on".
+pub fn preposition(placement: Placement) -> &'static str {
+    match placement {
+        Placement::In => "in",
+        Placement::On => "on",
+    }
+}
+
+/// Is the room dark?  It is if it's flagged as Dark, and there's no lit light source
+/// in it: on the floor, in the player's hands, or visible in or on something else.
+pub fn is_dark(world: &World, room: ID) -> bool {
+    world.has_flag(room, Dark) && !has_light(world, room)
+}
+
+/// Is there a lit light source among the container's contents, or visible in or on
+/// them, or carried by a player among them?
+fn has_light(world: &World, container: ID) -> bool {
+    contents(world, container).into_iter().any(|id| {
+        (world.has_flag(id, LightSource) && world.has_flag(id, Lit))
+            || ((shows_contents(world, id) || world.is_player(id)) && has_light(world, id))
+    })
+}
+
+/// Can things be put in or on the thing?  If so, and it isn't closed, its contents
+/// are visible.
+pub fn shows_contents(world: &World, thing: ID) -> bool {
+    world.has_inventory(thing)
+        && (world.has_flag(thing, Container) || world.has_flag(thing, Supporter))
+        && !is_closed(world, thing)
+}
+
 /// Tries to follow a link in the given direction; returns the linked
 /// location if any.
 pub fn follow_link(world: &World, loc: ID, dir: Dir) -> Option<LinkDest> {
@@ -39,6 +178,20 @@ pub fn follow_link(world: &World, loc: ID, dir: Dir) -> Option<LinkDest> {
     roomc.links.get(&dir).cloned()
 }
 
+/// Returns the doors the room links to.
+pub fn doors(world: &World, room: ID) -> BTreeSet<ID> {
+    assert_is_room(world, room);
+
+    world.rooms[&room]
+        .links
+        .values()
+        .filter_map(|dest| match dest {
+            LinkDest::Door(door) => Some(*door),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Determines whether the thing is in the container.
 ///
 /// * Panics if the container has no inventory component.
@@ -89,25 +242,11 @@ pub fn non_scenery(world: &World, owner: ID) -> BTreeSet<ID> {
     result
 }
 
-pub fn immovable(world: &World, owner: ID) -> BTreeSet<ID> {
-    assert_has_inventory(world, owner);
-
-    let mut result: BTreeSet<ID> = BTreeSet::new();
-
-    // FIRST, get everything that's flagged as immovable.
-    for id in contents(world, owner) {
-        if world.has_flag(id, Immovable) {
-            result.insert(id);
-        }
-    }
-
-    result
-}
-
 
 /// Finds all things in the viewer's location that are visible to
 /// the viewer.  This includes things owned by the viewer, present
-/// in the viewer's location, or (ultimately) visible in open containers.
+/// in the viewer's location, doors leading out of it, or (ultimately) visible
+/// in or on containers and supporters.
 pub fn visible(world: &World, viewer: ID) -> BTreeSet<ID> {
     let mut result: BTreeSet<ID> = BTreeSet::new();
 
@@ -116,14 +255,47 @@ pub fn visible(world: &World, viewer: ID) -> BTreeSet<ID> {
         result.append(&mut contents(world, viewer));
     }
 
-    // NEXT, get anything in the viewer's location.
+    // NEXT, get anything in the viewer's location, and any doors leading out of it,
+    // unless it's too dark to see them.
     if world.has_location(viewer) {
-        result.append(&mut contents(world, loc(world, viewer)));
+        let here = loc(world, viewer);
+
+        if !world.is_room(here) {
+            result.append(&mut contents(world, here));
+        } else if !is_dark(world, here) {
+            result.append(&mut contents(world, here));
+            result.append(&mut doors(world, here));
+        }
+    }
+
+    // NEXT, get anything in or on the visible things, and so on down.
+    let mut pending: Vec<ID> = result.iter().cloned().collect();
+
+    while let Some(id) = pending.pop() {
+        if id != viewer && shows_contents(world, id) {
+            for inner in contents(world, id) {
+                if result.insert(inner) {
+                    pending.push(inner);
+                }
+            }
+        }
     }
 
     result
 }
 
+/// Finds all visible things that the viewer could, in theory, take: things he
+/// isn't already carrying, whether they are in his location or in or on something
+/// else, that aren't flagged as immovable.
+pub fn takeable(world: &World, viewer: ID) -> BTreeSet<ID> {
+    let held = contents(world, viewer);
+
+    visible(world, viewer)
+        .into_iter()
+        .filter(|id| *id != viewer && !held.contains(id) && !world.has_flag(*id, Immovable))
+        .collect()
+}
+
 /// Finds all things in the location's inventory that can be removed,
 /// i.e., that isn't flagged as Immovable.
 pub fn removable(world: &World, loc: ID) -> BTreeSet<ID> {
@@ -177,59 +349,157 @@ pub fn gettable(world: &World, viewer: ID) -> BTreeSet<ID> {
 
 /// Removes the thing from its current location and puts it in LIMBO.
 pub fn take_out(world: &mut World, thing: ID) {
-    let container = loc(world, thing);
+    place(world, thing, LIMBO, Placement::In);
+}
 
-    // FIRST, remove it from wherever
-    world.inventories.get_mut(&container).unwrap().remove(thing);
+/// Puts the thing in the container, removing it from its current location.
+pub fn put_in(world: &mut World, thing: ID, container: ID) {
+    place(world, thing, container, Placement::In);
+}
 
-    // NEXT, put it in LIMBO
-    world.locations.get_mut(&thing).unwrap().id = LIMBO;
-    world.inventories.get_mut(&LIMBO).unwrap().add(thing);
+/// Puts the thing on the supporter, removing it from its current location.
+pub fn put_on(world: &mut World, thing: ID, supporter: ID) {
+    place(world, thing, supporter, Placement::On);
 }
 
-pub fn put_in(world: &mut World, thing: ID, container: ID) {
+/// Moves the thing to its new location with the given placement, keeping the
+/// location and inventory components consistent.
+fn place(world: &mut World, thing: ID, dest: ID, placement: Placement) {
     // FIRST, remove it from wherever.
     let there = loc(world, thing);
     world.inventories.get_mut(&there).unwrap().remove(thing);
 
     // NEXT, put it where it goes.
-    world.locations.get_mut(&thing).unwrap().id = container;
-    world.inventories.get_mut(&container).unwrap().add(thing);
+    let locc = world.locations.get_mut(&thing).unwrap();
+    locc.id = dest;
+    locc.placement = placement;
+    world.inventories.get_mut(&dest).unwrap().add(thing);
 }
 
 //---------------------------------------------------------------------------------
 // High-level operations
 
-/// The player tries to enter the room.
+/// The player tries to enter the room.  If he's leaving a dark room, the rules
+/// can stop him, or react when he goes.
 pub fn enter_room(world: &mut World, pid: ID, room: ID) -> PhysResult {
-    if rule::allows(world, &EnterRoom(pid, room)) {
-        put_in(world, pid, room);
+    let from = loc(world, pid);
+    let in_dark = world.is_room(from) && is_dark(world, from);
 
-        if !world.has_flag(pid, Seen(room)) {
-            visual::room(world, room);
-        } else {
-            visual::room_brief(world, room);
-        }
+    if in_dark && !rule::allows(world, &MoveInDark(pid, from)) {
+        return Ok(());
+    }
 
-        world.set_flag(pid, Seen(room));
+    if rule::allows(world, &EnterRoom(pid, room)) {
+        // What happens as he leaves the dark happens before he arrives.
+        if in_dark {
+            rule::fire_event(world, &MoveInDark(pid, from));
+        }
 
+        put_in(world, pid, room);
+        describe_room(world, pid, room);
         rule::fire_event(world, &EnterRoom(pid, room));
     }
 
     Ok(())
 }
 
-/// The player gets the thing.
-pub fn get_thing(world: &mut World, pid: ID, thing: ID) -> PhysResult {
+/// Describes the room the player is in: in full, if he hasn't seen it before, and
+/// briefly otherwise.  He hasn't seen a room until he's been in it when it isn't dark.
+pub fn describe_room(world: &mut World, pid: ID, room: ID) {
+    if !world.has_flag(pid, Seen(room)) {
+        visual::room(world, room);
+    } else {
+        visual::room_brief(world, room);
+    }
+
+    if !is_dark(world, room) {
+        world.set_flag(pid, Seen(room));
+    }
+}
+
+/// The player gets the thing.  The acknowledgement, e.g., "Taken.", is output if he
+/// succeeds, before any rules fire.
+pub fn get_thing(world: &mut World, pid: ID, thing: ID, ack: &str) -> PhysResult {
+    check_capacity(world, thing, pid, Placement::In)?;
+
     if rule::allows(world, &GetThing(pid, thing)) {
         put_in(world, thing, pid);
-        visual::act("Taken.");
+        visual::act(world, ack);
         rule::fire_event(world, &GetThing(pid, thing));
     }
 
     Ok(())
 }
 
+/// The player puts the thing in or on the destination.  The acknowledgement is
+/// output if he succeeds, before any rules fire.
+pub fn put_thing(
+    world: &mut World,
+    pid: ID,
+    thing: ID,
+    dest: ID,
+    placement: Placement,
+    ack: &str,
+) -> PhysResult {
+    check_capacity(world, thing, dest, placement)?;
+
+    let event = match placement {
+        Placement::In => PutIn(pid, thing, dest),
+        Placement::On => PutOn(pid, thing, dest),
+    };
+
+    if rule::allows(world, &event) {
+        place(world, thing, dest, placement);
+        visual::act(world, ack);
+        rule::fire_event(world, &event);
+    }
+
+    Ok(())
+}
+
+/// The player opens the thing.
+pub fn open_thing(world: &mut World, pid: ID, thing: ID, ack: &str) -> PhysResult {
+    change_flag(world, &OpenThing(pid, thing), thing, Open, true, ack)
+}
+
+/// The player closes the thing.
+pub fn close_thing(world: &mut World, pid: ID, thing: ID, ack: &str) -> PhysResult {
+    change_flag(world, &CloseThing(pid, thing), thing, Open, false, ack)
+}
+
+/// The player locks the thing with its key.
+pub fn lock_thing(world: &mut World, pid: ID, thing: ID, ack: &str) -> PhysResult {
+    change_flag(world, &LockThing(pid, thing), thing, Locked, true, ack)
+}
+
+/// The player unlocks the thing with its key.
+pub fn unlock_thing(world: &mut World, pid: ID, thing: ID, ack: &str) -> PhysResult {
+    change_flag(world, &UnlockThing(pid, thing), thing, Locked, false, ack)
+}
+
+/// If the rules allow the event, sets or clears the thing's flag, outputs the
+/// acknowledgement, and fires the event.
+fn change_flag(
+    world: &mut World,
+    event: &Event,
+    thing: ID,
+    flag: Flag,
+    value: bool,
+    ack: &str,
+) -> PhysResult {
+    if rule::allows(world, event) {
+        if value {
+            world.set_flag(thing, flag);
+        } else {
+            world.unset_flag(thing, flag);
+        }
+        visual::act(world, ack);
+        rule::fire_event(world, event);
+    }
+
+    Ok(())
+}
+
 /// The player reads the thing's Book prose.
 pub fn read_thing(world: &mut World, pid: ID, thing: ID) -> PhysResult {
     if rule::allows(world, &ReadThing(pid, thing)) {
@@ -266,3 +536,37 @@ fn assert_has_location(world: &World, thing: ID) {
         idtag(world, thing)
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_builder::WorldBuilder;
+
+    #[test]
+    fn capacity() {
+        let mut wb = WorldBuilder::new();
+        wb.player().location("room").max_weight(5);
+        wb.room("room", "Room");
+        wb.thing("sack", "sack", "sack").location("room").container().max_size(2).weight(1);
+        wb.thing("brick", "brick", "brick").location("room").size(2).weight(3);
+        wb.thing("pen", "pen", "pen").location("room").size(1).weight(1);
+        let mut world = wb.world();
+        let (pid, sack) = (world.pid, world.lookup("sack"));
+        let (brick, pen) = (world.lookup("brick"), world.lookup("pen"));
+
+        // The brick fills the sack, and the full sack weighs 4.
+        assert!(check_capacity(&world, brick, sack, Placement::In).is_ok());
+        put_in(&mut world, brick, sack);
+        assert!(check_capacity(&world, pen, sack, Placement::In).is_err());
+        assert_eq!(weight(&world, sack), 4);
+
+        // The player can carry the sack and the pen, but nothing more.
+        put_in(&mut world, sack, pid);
+        assert!(check_capacity(&world, pen, pid, Placement::In).is_ok());
+        put_in(&mut world, pen, pid);
+        assert_eq!(load_weight(&world, pid), 5);
+
+        // Moving the brick out of the sack into his hands adds no weight.
+        assert!(check_capacity(&world, brick, pid, Placement::In).is_ok());
+    }
+}
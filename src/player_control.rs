@@ -1,12 +1,16 @@
 //! The Player Control System
 
-use crate::script::Script;
 use self::Status::*;
 use crate::command;
 use crate::command::Command;
 use crate::debug;
 use crate::entity::ID;
+use crate::grammar;
+use crate::grammar::Arg;
+use crate::grammar::Pattern;
+use crate::grammar::Slot;
 use crate::phys;
+use crate::script::Script;
 use crate::types::Dir::*;
 use crate::types::Flag::*;
 use crate::types::*;
@@ -14,18 +18,28 @@ use crate::visual;
 use crate::world::*;
 use crate::Game;
 use std::collections::BTreeSet;
+use std::collections::VecDeque;
 
 /// A status result.  Indicates the general category of the change.
 #[derive(Copy, Clone, Debug)]
 enum Status {
     /// Normal response: the world has been updated, and the change can be undone.
-    Normal,
+    /// The command took the given number of turns.
+    Normal(Time),
 
     /// Restart response; the game should be restarted from scratch.
     Restart,
 
-    /// Undo the last command (plus anything that happened after, e.g., rule firings)
+    /// The command undid or redid earlier commands, or restored a saved game; the
+    /// game's history has already been updated.
     Undo,
+
+    /// The player has quit the game.
+    Quit,
+
+    /// A meta-command that doesn't change the world, e.g., starting a transcript.
+    /// It takes no time and can't be undone.
+    Meta,
 }
 
 /// A status result, used for special commands
@@ -37,9 +51,65 @@ struct Player {
     pub loc: ID,
 }
 
-/// The Player Control system.  Processes player commands.
+/// The Player Control system.  Processes the player's input, which may contain
+/// several commands.  The commands are queued and executed in order, and time passes
+/// after each.  The queue stops at the first error, or when something interrupts
+/// the player, e.g., a guard denies an action or the player dies; the player is told
+/// which commands were skipped.
 pub fn system(game: &mut Game, input: &str) {
-    // FIRST, get the current game state, for later undo.
+    let mut queue: VecDeque<String> = command::split(input).into();
+
+    while let Some(input) = queue.pop_front() {
+        game.world.interrupt = false;
+
+        let time = execute(game, &input);
+
+        if game.world.status == GameStatus::Quit {
+            return;
+        }
+
+        game.pass_time(time);
+
+        if !queue.is_empty() && (game.world.interrupt || game.world.status != GameStatus::Playing) {
+            let skipped: Vec<String> = queue.drain(..).collect();
+            visual::info(&game.world, &format!("Skipped: {}", skipped.join(". ")));
+        }
+    }
+}
+
+/// Executes a single command, returning the number of turns that elapsed.  Errors,
+/// meta-commands like "undo" and "save", and debugging commands take no time.
+fn execute(game: &mut Game, input: &str) -> Time {
+    // FIRST, if the player was asked which thing he meant, the input might be the
+    // answer; if so, it completes the command that raised the question.
+    let input = match game.world.question.take() {
+        Some(question) => answer(&game.world, &question, input).unwrap_or_else(|| input.into()),
+        None => input.into(),
+    };
+
+    // NEXT, if the input is "oops", it corrects the unknown word in the previous
+    // command, which is then tried again.
+    let misspelling = game.world.misspelling.take();
+    let input = match correct(&game.world, misspelling, &input) {
+        Ok(input) => input,
+        Err(msg) => {
+            visual::error(&game.world, &msg);
+            game.world.interrupt = true;
+            return 0;
+        }
+    };
+
+    // NEXT, if the input is "again", repeat the last command.
+    let input = match repeat(game, &input) {
+        Ok(input) => input,
+        Err(msg) => {
+            visual::error(&game.world, &msg);
+            game.world.interrupt = true;
+            return 0;
+        }
+    };
+
+    // NEXT, get the current game state, for later undo.
     let undo_info = game.world.clone();
 
     // NEXT, get the player's context
@@ -48,84 +118,321 @@ pub fn system(game: &mut Game, input: &str) {
         loc: phys::loc(&game.world, game.world.pid),
     };
 
-    // NEXT, handle the input
-    let result = handle_input(game, &player, input);
-    match result {
-        Err(msg) => visual::error(&msg),
-        Ok(Normal) => {
-            game.save_for_undo(undo_info);
+    // NEXT, parse the input.
+    let cmd = match command::parse(&game.world, &input) {
+        Ok(cmd) => cmd,
+        Err(msg) => {
+            visual::error(&game.world, &msg);
+            game.world.interrupt = true;
+            return 0;
+        }
+    };
+
+    // NEXT, handle the command
+    match handle_command(game, &player, &cmd) {
+        Err(msg) => {
+            // If the command raised a question, remember the command so that the
+            // answer can complete it.
+            if let Some(question) = &mut game.world.question {
+                question.words = cmd.words.clone();
+            }
+            visual::error(&game.world, &msg);
+            game.world.interrupt = true;
+            0
+        }
+        Ok(Normal(time)) => {
+            game.save_for_undo(&cmd.input, undo_info);
+            if !cmd.is_debug {
+                game.last_command = Some(cmd);
+            }
+            time
+        }
+        Ok(Restart) => {
+            // Any remaining commands were meant for the old game.
+            game.restart();
+            game.world.interrupt = true;
+            0
         }
+        Ok(Undo) | Ok(Meta) => 0,
+        Ok(Quit) => {
+            game.world.status = GameStatus::Quit;
+            0
+        }
+    }
+}
+
+/// Processes the player's input once the game has been won or lost.  The player
+/// can only restart, restore a saved game, undo the last command, or quit.
+pub fn game_over(game: &mut Game, input: &str) {
+    let words: Vec<String> = match command::parse(&game.world, input) {
+        Ok(cmd) => cmd.words,
+        Err(_) => Vec::new(),
+    };
+    let words: Vec<&str> = words.iter().map(|s| s.as_ref()).collect();
+
+    let result = match words.as_slice() {
+        ["restart"] => cmd_restart(&game.world),
+        ["restore", name] => cmd_restore(game, name),
+        ["undo"] if game.world.allow_undo => cmd_undo(game, 1),
+        ["quit"] => cmd_quit(&game.world),
+        _ => Err(game_over_prompt(&game.world)),
+    };
+
+    match result {
+        Err(msg) => visual::error(&game.world, &msg),
         Ok(Restart) => game.restart(),
-        Ok(Undo) => game.undo(),
+        Ok(Quit) => game.world.status = GameStatus::Quit,
+        Ok(_) => (),
     }
 }
 
-fn handle_input(game: &mut Game, player: &Player, input: &str) -> StatusResult {
-    // FIRST, parse the input.
+/// If the input answers the question, i.e., consists entirely of nouns and adjectives
+/// of the things the player was asked to choose between, returns the question's
+/// command with the answer added to the ambiguous noun phrase.
+fn answer(world: &World, question: &Question, input: &str) -> Option<String> {
+    let cmd = command::parse(world, input).ok()?;
+
+    let answers = !cmd.words.is_empty()
+        && cmd.words.iter().all(|word| {
+            question
+                .candidates
+                .iter()
+                .any(|id| world.things[id].has_word(word))
+        });
+
+    let len = question.phrase.len();
+
+    if !answers || question.words.len() < len {
+        return None;
+    }
+
+    // Insert the answer just before the ambiguous noun phrase.
+    let pos = (0..=question.words.len() - len)
+        .find(|i| question.words[*i..*i + len] == question.phrase[..])
+        .unwrap_or(question.words.len() - len);
+
+    let mut words = question.words[..pos].to_vec();
+    words.extend(cmd.words);
+    words.extend_from_slice(&question.words[pos..]);
+    Some(words.join(" "))
+}
+
+/// If the input is an "oops" command, returns the misspelled command with the
+/// correction in place of the unknown word.  Otherwise, returns the input unchanged.
+fn correct(world: &World, misspelling: Option<Misspelling>, input: &str) -> Result<String, String> {
+    let cmd = command::parse(world, input)?;
+
+    match (cmd.words.first().map(|w| w.as_str()), misspelling) {
+        (Some("oops"), None) => Err("There's nothing to correct.".into()),
+        (Some("oops"), Some(_)) if cmd.words.len() != 2 => {
+            Err("Say \"oops\" and the word you meant.".into())
+        }
+        (Some("oops"), Some(mut misspelling)) => {
+            misspelling.words[misspelling.index] = cmd.words[1].clone();
+            Ok(misspelling.words.join(" "))
+        }
+        _ => Ok(input.into()),
+    }
+}
+
+/// If the input is "again", returns the input of the last command that changed the
+/// world.  Otherwise, returns the input unchanged.
+fn repeat(game: &Game, input: &str) -> Result<String, String> {
     let cmd = command::parse(&game.world, input)?;
 
+    if cmd.words != ["again"] {
+        return Ok(input.into());
+    }
+
+    match &game.last_command {
+        Some(last) => Ok(last.input.clone()),
+        None => Err("There's nothing to repeat.".into()),
+    }
+}
+
+/// The question put to the player when the game is over.
+pub fn game_over_prompt(world: &World) -> String {
+    if world.allow_undo {
+        "Would you like to RESTART, RESTORE a saved game, UNDO your last move, or QUIT?".into()
+    } else {
+        "Would you like to RESTART, RESTORE a saved game, or QUIT?".into()
+    }
+}
+
+fn handle_command(game: &mut Game, player: &Player, cmd: &Command) -> StatusResult {
     if cmd.is_debug {
-        handle_debug_command(game, player, &cmd)
+        handle_debug_command(game, player, cmd)
     } else {
-        handle_normal_command(game, player, &cmd)
+        handle_normal_command(game, player, cmd)
     }
 }
 
 fn handle_normal_command(game: &mut Game, player: &Player, cmd: &Command) -> StatusResult {
-    let world = &mut game.world;
     let the_words: Vec<&str> = cmd.words.iter().map(|s| s.as_ref()).collect();
     let words = the_words.as_slice();
 
-    // FIRST, handle custom commands.
-    // TODO: Possible better way to handle this: write function that matches the words
-    // and clones the specific handler.
-    for handler in world.command_handlers.clone() {
-        if handler.matches(words) {
-            handler.execute(&mut game.world, player, words)?;
-            return Ok(Normal);
-        }
-    }
-
-    // NEXT, handle built-in commands
-    match words {
-        ["go", "north"] => cmd_go(world, player, North),
-        ["north"] => cmd_go(world, player, North),
-        ["go", "south"] => cmd_go(world, player, South),
-        ["south"] => cmd_go(world, player, South),
-        ["go", "east"] => cmd_go(world, player, East),
-        ["east"] => cmd_go(world, player, East),
-        ["go", "west"] => cmd_go(world, player, West),
-        ["west"] => cmd_go(world, player, West),
-        ["help"] => cmd_help(),
-        ["look"] => cmd_look(world, player),
-        ["inventory"] => cmd_inventory(world, player),
-        ["examine", name] => cmd_examine(world, player, name),
-        ["read", name] => cmd_read(world, player, name),
-        ["get", name] => cmd_get(world, player, name),
-        ["pick", "up", name] => cmd_get(world, player, name),
-        ["drop", name] => cmd_drop(world, player, name),
-        ["undo"] => cmd_undo(game),
-        ["restart"] => cmd_restart(),
-        ["quit"] => cmd_quit(),
+    // FIRST, find the first handler whose pattern matches the words and whose slots
+    // can be resolved.  If none can be resolved, the first matching handler's error
+    // explains why.
+    let mut first_error: Option<String> = None;
 
-        // Error
-        _ => Err("I don't understand.".into()),
+    for i in 0..game.world.command_handlers.len() {
+        let handler = game.world.command_handlers[i].clone();
+
+        let slots = match handler.pattern.matches(words) {
+            Some(slots) => slots,
+            None => continue,
+        };
+
+        match resolve_slots(&mut game.world, player, &slots)? {
+            Ok(args) => return handler.execute(game, player, words, &args),
+            Err(msg) => {
+                first_error.get_or_insert(msg);
+            }
+        }
     }
+
+    // NEXT, nothing matched; perhaps the player misspelled something.  If so, he
+    // can correct it with "oops".
+    let scope = phys::visible(&game.world, player.id);
+    if let Some((index, msg)) = command::unknown_word(&game.world, &cmd.words, &scope) {
+        game.world.misspelling = Some(Misspelling {
+            words: cmd.words.clone(),
+            index,
+        });
+        return Err(msg);
+    }
+
+    Err(first_error.unwrap_or_else(|| "I don't understand.".into()))
+}
+
+/// The directions the player can go, by verb.
+const DIRECTIONS: [(&str, Dir); 12] = [
+    ("north", North),
+    ("south", South),
+    ("east", East),
+    ("west", West),
+    ("northeast", Northeast),
+    ("northwest", Northwest),
+    ("southeast", Southeast),
+    ("southwest", Southwest),
+    ("up", Up),
+    ("down", Down),
+    ("in", In),
+    ("out", Out),
+];
+
+/// The built-in commands, in order of precedence.
+pub fn builtin_commands() -> Vec<CommandHandler> {
+    let mut handlers = Vec::new();
+
+    for (word, _) in &DIRECTIONS {
+        handlers.push(CommandHandler::builtin(word, |game, player, words, _| {
+            cmd_go(&mut game.world, player, direction(words[0]))
+        }));
+    }
+
+    handlers.push(CommandHandler::builtin("help", |game, _, _, _| cmd_help(&game.world)));
+    handlers.push(CommandHandler::builtin("look", |game, player, _, _| {
+        cmd_look(&game.world, player)
+    }));
+    handlers.push(CommandHandler::builtin("inventory", |game, player, _, _| {
+        cmd_inventory(&game.world, player)
+    }));
+    handlers.push(CommandHandler::builtin("examine {visible}", |game, player, _, args| {
+        cmd_examine(&game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("read {taken}", |game, player, _, args| {
+        cmd_read(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("get all", |game, player, _, _| {
+        cmd_get_all(&mut game.world, player, None)
+    }));
+    handlers.push(CommandHandler::builtin("get all except {here}", |game, player, _, args| {
+        cmd_get_all(&mut game.world, player, Some(thing_arg(args, 0)))
+    }));
+    handlers.push(CommandHandler::builtin("drop all", |game, player, _, _| {
+        cmd_drop_all(&mut game.world, player, None)
+    }));
+    handlers.push(CommandHandler::builtin("drop all except {held}", |game, player, _, args| {
+        cmd_drop_all(&mut game.world, player, Some(thing_arg(args, 0)))
+    }));
+    handlers.push(CommandHandler::builtin("get {here} from {visible}", |game, player, _, args| {
+        cmd_get_from(&mut game.world, player, thing_arg(args, 0), thing_arg(args, 1))
+    }));
+    handlers.push(CommandHandler::builtin("get {here}", |game, player, _, args| {
+        cmd_get(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("drop {held}", |game, player, _, args| {
+        cmd_drop(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("put {taken} in {container}", |game, player, _, args| {
+        cmd_put(&mut game.world, player, thing_arg(args, 0), thing_arg(args, 1), Placement::In)
+    }));
+    handlers.push(CommandHandler::builtin("put {taken} on {supporter}", |game, player, _, args| {
+        cmd_put(&mut game.world, player, thing_arg(args, 0), thing_arg(args, 1), Placement::On)
+    }));
+    handlers.push(CommandHandler::builtin("open {visible}", |game, player, _, args| {
+        cmd_open(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("close {visible}", |game, player, _, args| {
+        cmd_close(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("lock {visible} with {taken}", |game, player, _, args| {
+        cmd_lock(&mut game.world, player, thing_arg(args, 0), thing_arg(args, 1))
+    }));
+    handlers.push(CommandHandler::builtin("unlock {visible} with {taken}", |game, player, _, args| {
+        cmd_unlock(&mut game.world, player, thing_arg(args, 0), thing_arg(args, 1))
+    }));
+    handlers.push(CommandHandler::builtin("light {taken}", |game, player, _, args| {
+        cmd_light(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("extinguish {visible}", |game, player, _, args| {
+        cmd_extinguish(&mut game.world, player, thing_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("undo", |game, _, _, _| cmd_undo(game, 1)));
+    handlers.push(CommandHandler::builtin("undo {word}", |game, _, _, args| {
+        cmd_undo(game, parse_count(word_arg(args, 0))?)
+    }));
+    handlers.push(CommandHandler::builtin("redo", |game, _, _, _| cmd_redo(game)));
+    handlers.push(CommandHandler::builtin("save {word}", |game, _, _, args| {
+        cmd_save(game, word_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("restore {word}", |game, _, _, args| {
+        cmd_restore(game, word_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("score", |game, _, _, _| cmd_score(&game.world)));
+    handlers.push(CommandHandler::builtin("full", |game, _, _, _| cmd_full_score(&game.world)));
+    handlers.push(CommandHandler::builtin("full score", |game, _, _, _| {
+        cmd_full_score(&game.world)
+    }));
+    handlers.push(CommandHandler::builtin("script on", |game, _, _, _| {
+        cmd_script_on(game, "transcript")
+    }));
+    handlers.push(CommandHandler::builtin("script on {word}", |game, _, _, args| {
+        cmd_script_on(game, word_arg(args, 0))
+    }));
+    handlers.push(CommandHandler::builtin("script off", |game, _, _, _| cmd_script_off(game)));
+    handlers.push(CommandHandler::builtin("restart", |game, _, _, _| cmd_restart(&game.world)));
+    handlers.push(CommandHandler::builtin("quit", |game, _, _, _| cmd_quit(&game.world)));
+
+    handlers
 }
 
 // User Commands
 
 
 /// Display basic help, i.e., what commands are available.
-fn cmd_help() -> StatusResult {
+fn cmd_help(world: &World) -> StatusResult {
     visual::info(
+        world,
         "\
 You've got the usual commands: n, s, e, w, look, get, drop, quit.
 You know.  Like that.
     ",
     );
 
-    Ok(Normal)
+    Ok(Meta)
 }
 
 /// Move the player in the given direction
@@ -133,11 +440,30 @@ fn cmd_go(world: &mut World, player: &Player, dir: Dir) -> StatusResult {
     match phys::follow_link(world, player.loc, dir) {
         Some(LinkDest::Room(dest)) => {
             phys::enter_room(world, player.id, dest)?;
-            Ok(Normal)
-        },
+            Ok(Normal(1))
+        }
+        Some(LinkDest::Door(door)) => {
+            // The player opens a closed door on the way through, if he can.
+            if phys::is_closed(world, door) {
+                if world.has_flag(door, Locked) {
+                    return Err(format!("The {} is closed.", world.things[&door].name));
+                }
+
+                let ack = format!("(first opening the {})", world.things[&door].name);
+                phys::open_thing(world, player.id, door, &ack)?;
+
+                if phys::is_closed(world, door) {
+                    return Ok(Normal(1));
+                }
+            }
+
+            let dest = world.doors[&door].other_side(player.loc);
+            phys::enter_room(world, player.id, dest)?;
+            Ok(Normal(1))
+        }
         Some(LinkDest::DeadEnd(prose)) => {
-            visual::info(&prose);
-            Ok(Normal)
+            visual::info(world, &prose);
+            Ok(Normal(1))
         }
         None => {
             Err("You can't go that way.".into())
@@ -148,106 +474,432 @@ fn cmd_go(world: &mut World, player: &Player, dir: Dir) -> StatusResult {
 /// Re-describe the current location.
 fn cmd_look(world: &World, player: &Player) -> StatusResult {
     visual::room(world, player.loc);
-    Ok(Normal)
+    Ok(Normal(1))
 }
 
 /// Display the player's inventory.
 fn cmd_inventory(world: &World, player: &Player) -> StatusResult {
     visual::player_inventory(world, player.id);
-    Ok(Normal)
+    Ok(Meta)
 }
 
 /// Describe a thing in the current location.
-fn cmd_examine(world: &World, player: &Player, name: &str) -> StatusResult {
-    if let Some(thing) = find_noun(world, phys::visible(world, player.id), name) {
-        if thing == player.id {
-            visual::player(world, player.id);
-        } else {
-            visual::thing(world, thing);
-        }
-        Ok(Normal)
+fn cmd_examine(world: &World, player: &Player, thing: ID) -> StatusResult {
+    if phys::is_dark(world, player.loc) {
+        return Err("It's too dark to see.".into());
+    }
+
+    if thing == player.id {
+        visual::player(world, player.id);
     } else {
-        Err("You don't see any such thing.".into())
+        visual::thing(world, thing);
     }
+    Ok(Normal(1))
 }
 
 /// Read a thing in the current location.
-fn cmd_read(world: &mut World, player: &Player, name: &str) -> StatusResult {
-    if let Some(thing) = find_noun(world, phys::visible(world, player.id), name) {
-        // If it has no prose, it can't be read
-        if !visual::can_read(world, thing) {
-            return Err("You can't read that.".into());
-        }
+fn cmd_read(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    if phys::is_dark(world, player.loc) {
+        return Err("It's too dark to read.".into());
+    }
 
-        // If he's holding it, or it's immovable, then he can read it.
-        if phys::owns(world, player.id, thing) || world.has_flag(thing, Immovable) {
-            phys::read_thing(world, player.id, thing)?;
-            Ok(Normal)
-        } else {
-            Err("You don't have it.".into())
-        }
+    // If it has no prose, it can't be read
+    if !visual::can_read(world, thing) {
+        return Err("You can't read that.".into());
+    }
+
+    // If he's holding it, or it's immovable, then he can read it.
+    if phys::owns(world, player.id, thing) || world.has_flag(thing, Immovable) {
+        phys::read_thing(world, player.id, thing)?;
+        Ok(Normal(1))
     } else {
-        // It isn't here.
-        Err("You don't see any such thing.".into())
+        Err("You don't have it.".into())
     }
 }
 
 /// Gets a thing from the location's inventory.
-fn cmd_get(world: &mut World, player: &Player, noun: &str) -> StatusResult {
-    // Does he already have it?
-    if find_noun(world, phys::contents(world, player.id), noun).is_some() {
-        return Err("You already have that.".into());
+fn cmd_get(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    if world.has_flag(thing, Immovable) {
+        return Err("You can't take that!".into());
     }
 
-    if find_noun(world, phys::immovable(world, player.loc), noun).is_some() {
-        return Err("You can't take that!".into());
+    phys::get_thing(world, player.id, thing, "Taken.")?;
+    Ok(Normal(1))
+}
+
+/// Gets a thing from the container or supporter it's in or on.
+fn cmd_get_from(world: &mut World, player: &Player, thing: ID, source: ID) -> StatusResult {
+    if !phys::shows_contents(world, source) {
+        return Err(format!("You can't take things from the {}.", world.things[&source].name));
     }
 
-    if let Some(thing) = find_noun(world, phys::gettable(world, player.id), noun) {
-        // Get the thing.
-        phys::get_thing(world, player.id, thing)?;
-        return Ok(Normal);
+    if phys::loc(world, thing) != source {
+        let placement = if world.has_flag(source, Container) {
+            Placement::In
+        } else {
+            Placement::On
+        };
+
+        return Err(format!(
+            "The {} isn't {} the {}.",
+            world.things[&thing].name,
+            phys::preposition(placement),
+            world.things[&source].name
+        ));
     }
 
-    Err("You don't see any such thing.".into())
+    cmd_get(world, player, thing)
+}
+
+/// Gets everything in the location that can be taken, except the given thing.
+/// Each thing is acknowledged by name, and the rules fire for each in turn; if
+/// the player dies or the game ends, he stops.
+fn cmd_get_all(world: &mut World, player: &Player, except: Option<ID>) -> StatusResult {
+    if phys::is_dark(world, player.loc) {
+        return Err("It's too dark to see what's here.".into());
+    }
+
+    let things = all_but(world, phys::gettable(world, player.id), player, except);
+
+    if things.is_empty() {
+        return Err("There's nothing here you can take.".into());
+    }
+
+    for thing in things {
+        if world.has_flag(player.id, Dead) || world.status != GameStatus::Playing {
+            break;
+        }
+
+        // A rule might have moved it.
+        if !phys::owns(world, player.loc, thing) {
+            continue;
+        }
+
+        // If he can't carry it, he says so and goes on to the next thing.
+        let ack = format!("{}: Taken.", world.things[&thing].name);
+        if let Err(msg) = phys::get_thing(world, player.id, thing, &ack) {
+            visual::error(world, &msg);
+        }
+    }
+
+    Ok(Normal(1))
 }
 
 /// Drops a thing you're carrying
-fn cmd_drop(world: &mut World, player: &Player, noun: &str) -> StatusResult {
-    if let Some(thing) = find_noun(world, phys::droppable(world, player.id), noun) {
-        // Drop the thing
-        phys::put_in(world, thing, player.loc);
-        visual::act("Dropped.");
-        Ok(Normal)
-    } else if find_noun(world, phys::scenery(world, player.id), noun).is_some() {
-        Err("You can't drop that!".into())
-    } else if find_noun(world, phys::visible(world, player.id), noun).is_some() {
-        Err("You aren't carrying that.".into())
-    } else {
-        Err("You don't see any such thing.".into())
+fn cmd_drop(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    if world.has_flag(thing, Immovable) || world.has_flag(thing, Scenery) {
+        return Err("You can't drop that!".into());
+    }
+
+    phys::put_in(world, thing, player.loc);
+    visual::act(world, "Dropped.");
+    Ok(Normal(1))
+}
+
+/// Puts a thing the player is carrying in a container or on a supporter.
+fn cmd_put(
+    world: &mut World,
+    player: &Player,
+    thing: ID,
+    dest: ID,
+    placement: Placement,
+) -> StatusResult {
+    let word = phys::preposition(placement);
+
+    if world.has_flag(thing, Immovable) || world.has_flag(thing, Scenery) {
+        return Err("You can't put that anywhere!".into());
+    }
+
+    if thing == dest || phys::encloses(world, thing, dest) {
+        return Err(format!("You can't put something {} itself.", word));
+    }
+
+    if phys::is_closed(world, dest) {
+        return Err(format!("The {} is closed.", world.things[&dest].name));
+    }
+
+    let ack = format!(
+        "You put the {} {} the {}.",
+        world.things[&thing].name, word, world.things[&dest].name
+    );
+    phys::put_thing(world, player.id, thing, dest, placement, &ack)?;
+    Ok(Normal(1))
+}
+
+/// Opens a thing that can be opened, if it isn't locked.
+fn cmd_open(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    let name = world.things[&thing].name.clone();
+
+    if !world.has_flag(thing, Openable) {
+        return Err("You can't open that.".into());
+    }
+
+    if world.has_flag(thing, Open) {
+        return Err(format!("The {} is already open.", name));
+    }
+
+    if world.has_flag(thing, Locked) {
+        return Err(format!("The {} is locked.", name));
+    }
+
+    phys::open_thing(world, player.id, thing, "Opened.")?;
+    Ok(Normal(1))
+}
+
+/// Closes a thing that can be opened.
+fn cmd_close(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    if !world.has_flag(thing, Openable) {
+        return Err("You can't close that.".into());
+    }
+
+    if !world.has_flag(thing, Open) {
+        return Err(format!("The {} is already closed.", world.things[&thing].name));
+    }
+
+    phys::close_thing(world, player.id, thing, "Closed.")?;
+    Ok(Normal(1))
+}
+
+/// Locks a closed thing with its key.
+fn cmd_lock(world: &mut World, player: &Player, thing: ID, key: ID) -> StatusResult {
+    let name = world.things[&thing].name.clone();
+
+    if !world.has_flag(thing, Lockable) {
+        return Err("You can't lock that.".into());
+    }
+
+    if world.has_flag(thing, Locked) {
+        return Err(format!("The {} is already locked.", name));
+    }
+
+    if world.has_flag(thing, Open) {
+        return Err(format!("You'll have to close the {} first.", name));
+    }
+
+    check_key(world, thing, key)?;
+    phys::lock_thing(world, player.id, thing, "Locked.")?;
+    Ok(Normal(1))
+}
+
+/// Unlocks a locked thing with its key.
+fn cmd_unlock(world: &mut World, player: &Player, thing: ID, key: ID) -> StatusResult {
+    if !world.has_flag(thing, Lockable) {
+        return Err("You can't unlock that.".into());
+    }
+
+    if !world.has_flag(thing, Locked) {
+        return Err(format!("The {} isn't locked.", world.things[&thing].name));
     }
+
+    check_key(world, thing, key)?;
+    phys::unlock_thing(world, player.id, thing, "Unlocked.")?;
+    Ok(Normal(1))
 }
 
-/// Undo the last command the game
-fn cmd_undo(game: &mut Game) -> StatusResult {
-    if game.has_undo() {
-        visual::act("Undone.");
-        Ok(Undo)
+/// Checks that the key fits the thing's lock.
+fn check_key(world: &World, thing: ID, key: ID) -> Result<(), String> {
+    if world.things[&thing].key == Some(key) {
+        Ok(())
     } else {
-        Err("Nothing to undo.".into())
+        Err(format!(
+            "The {} doesn't fit the {}.",
+            world.things[&key].name, world.things[&thing].name
+        ))
+    }
+}
+
+/// Lights a light source.  If that lights up a dark room, the player sees where
+/// he is.
+fn cmd_light(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    let name = world.things[&thing].name.clone();
+
+    if !world.has_flag(thing, LightSource) {
+        return Err("You can't light that.".into());
+    }
+
+    if world.has_flag(thing, Lit) {
+        return Err(format!("The {} is already lit.", name));
+    }
+
+    let was_dark = phys::is_dark(world, player.loc);
+    world.set_flag(thing, Lit);
+    visual::act(world, &format!("The {} is now lit.", name));
+
+    if was_dark && !phys::is_dark(world, player.loc) {
+        phys::describe_room(world, player.id, player.loc);
+    }
+
+    Ok(Normal(1))
+}
+
+/// Extinguishes a lit light source.
+fn cmd_extinguish(world: &mut World, player: &Player, thing: ID) -> StatusResult {
+    let name = world.things[&thing].name.clone();
+
+    if !world.has_flag(thing, LightSource) {
+        return Err("You can't extinguish that.".into());
+    }
+
+    if !world.has_flag(thing, Lit) {
+        return Err(format!("The {} isn't lit.", name));
+    }
+
+    world.unset_flag(thing, Lit);
+    visual::act(world, &format!("The {} is now dark.", name));
+
+    if phys::is_dark(world, player.loc) {
+        visual::act(world, "It is now pitch dark.");
+    }
+
+    Ok(Normal(1))
+}
+
+/// Drops everything the player is carrying that can be dropped, except the given
+/// thing.
+fn cmd_drop_all(world: &mut World, player: &Player, except: Option<ID>) -> StatusResult {
+    let things = all_but(world, phys::droppable(world, player.id), player, except);
+
+    if things.is_empty() {
+        return Err("You aren't carrying anything you can drop.".into());
+    }
+
+    for thing in things {
+        phys::put_in(world, thing, player.loc);
+        let ack = format!("{}: Dropped.", world.things[&thing].name);
+        visual::act(world, &ack);
+    }
+
+    Ok(Normal(1))
+}
+
+/// The things "all" refers to: the candidates, leaving out scenery, the player, and
+/// the exception, if any.
+fn all_but(world: &World, ids: BTreeSet<ID>, player: &Player, except: Option<ID>) -> Vec<ID> {
+    ids.into_iter()
+        .filter(|id| *id != player.id && Some(*id) != except && !world.has_flag(*id, Scenery))
+        .collect()
+}
+
+/// Undo the last count commands, or as many as can be undone.
+fn cmd_undo(game: &mut Game, count: usize) -> StatusResult {
+    if !game.world.allow_undo {
+        return Err("Sorry, you can't undo in this game.".into());
+    }
+
+    if !game.has_undo() {
+        return Err("Nothing to undo.".into());
+    }
+
+    for _ in 0..count {
+        if !game.has_undo() {
+            break;
+        }
+        let input = game.undo();
+        visual::act(&game.world, &format!("Undid: {}", input));
+    }
+
+    Ok(Undo)
+}
+
+/// Redo the last undone command.
+fn cmd_redo(game: &mut Game) -> StatusResult {
+    if !game.has_redo() {
+        return Err("Nothing to redo.".into());
     }
+
+    let input = game.redo();
+    visual::act(&game.world, &format!("Redid: {}", input));
+    Ok(Undo)
+}
+
+/// Save the game to a file
+fn cmd_save(game: &Game, name: &str) -> StatusResult {
+    game.save(&save_file(name))?;
+    visual::act(&game.world, "Saved.");
+    Ok(Meta)
+}
+
+/// Restore the game from a file, and describe where the player is.  The restore
+/// can be undone, but isn't a command to repeat.
+fn cmd_restore(game: &mut Game, name: &str) -> StatusResult {
+    let undo_info = game.world.clone();
+    game.restore(&save_file(name))?;
+    game.save_for_undo(&format!("restore {}", name), undo_info);
+
+    let world = &game.world;
+    visual::act(world, "Restored.");
+    visual::room(world, phys::loc(world, world.pid));
+    Ok(Undo)
+}
+
+/// Display the player's score.
+fn cmd_score(world: &World) -> StatusResult {
+    visual::info(world, &format!("{}.", score_text(world)?));
+    Ok(Meta)
+}
+
+/// Display the player's score, and the achievements for which it was awarded.
+fn cmd_full_score(world: &World) -> StatusResult {
+    if world.earned.is_empty() {
+        visual::info(world, &format!("{}.", score_text(world)?));
+        return Ok(Meta);
+    }
+
+    let mut text = format!("{}, as follows:", score_text(world)?);
+
+    for name in &world.earned {
+        text.push_str(&format!("|   {} points for {}", world.achievement(name).points, name));
+    }
+
+    visual::info(world, &text);
+    Ok(Meta)
+}
+
+/// The player's score, as a sentence.
+fn score_text(world: &World) -> Result<String, String> {
+    if world.achievements.is_empty() {
+        return Err("There's no score in this game.".into());
+    }
+
+    Ok(format!(
+        "You have scored {} of {} points in {} turns",
+        world.score, world.max_score, world.clock
+    ))
+}
+
+/// Start recording a transcript of the game to a file.
+fn cmd_script_on(game: &mut Game, name: &str) -> StatusResult {
+    if game.is_recording() {
+        return Err("You're already recording a transcript.".into());
+    }
+
+    let path = transcript_file(name);
+    game.start_transcript(&path)?;
+    visual::act(&game.world, &format!("Recording a transcript to {}.", path));
+    Ok(Meta)
+}
+
+/// Stop recording the transcript.
+fn cmd_script_off(game: &mut Game) -> StatusResult {
+    if !game.is_recording() {
+        return Err("You aren't recording a transcript.".into());
+    }
+
+    game.stop_transcript();
+    visual::act(&game.world, "Transcript stopped.");
+    Ok(Meta)
 }
 
 /// Restart the game
-fn cmd_restart() -> StatusResult {
-    visual::act("Restarting...");
+fn cmd_restart(world: &World) -> StatusResult {
+    visual::act(world, "Restarting...");
     Ok(Restart)
 }
 
 /// Quit the game.
-fn cmd_quit() -> StatusResult {
-    visual::act("Bye, then.");
-    ::std::process::exit(0);
+fn cmd_quit(world: &World) -> StatusResult {
+    visual::act(world, "Bye, then.");
+    Ok(Quit)
 }
 
 //------------------------------------------------------------------------------
@@ -273,14 +925,14 @@ fn handle_debug_command(game: &mut Game, player: &Player, cmd: &Command) -> Stat
 /// List all of the available entities.
 fn cmd_debug_list(world: &World) -> StatusResult {
     debug::list_world(world);
-    Ok(Normal)
+    Ok(Meta)
 }
 
 /// Dump information about the given entity, provided the ID string is valid.
 fn cmd_debug_dump(world: &World, id_arg: &str) -> StatusResult {
     let id = parse_id(world, id_arg)?;
     debug::dump_entity(world, id);
-    Ok(Normal)
+    Ok(Meta)
 }
 
 /// Describe the room as though the player were in it.
@@ -288,7 +940,7 @@ fn cmd_debug_look(world: &World, id_arg: &str) -> StatusResult {
     let id = parse_id(world, id_arg)?;
     if world.is_room(id) {
         visual::room(world, id);
-        Ok(Normal)
+        Ok(Meta)
     } else {
         Err(format!("Entity {} is not a room.", id))
     }
@@ -299,7 +951,7 @@ fn cmd_debug_examine(world: &World, id_arg: &str) -> StatusResult {
     let id = parse_id(world, id_arg)?;
     if world.is_thing(id) {
         visual::thing(world, id);
-        Ok(Normal)
+        Ok(Meta)
     } else {
         Err(format!("Entity {} is not a thing.", id))
     }
@@ -311,7 +963,7 @@ fn cmd_debug_go(world: &mut World, player: &Player, id_arg: &str) -> StatusResul
     if world.is_room(loc) {
         phys::put_in(world, player.id, loc);
         visual::room(world, loc);
-        Ok(Normal)
+        Ok(Normal(0))
     } else {
         Err(format!("Entity {} is not a room.", loc))
     }
@@ -343,89 +995,363 @@ fn parse_id(world: &World, token: &str) -> Result<ID, String> {
 //-------------------------------------------------------------------------
 // Parsing Tools
 
-/// Finds a noun in the list of things.
-fn find_noun(world: &World, ids: BTreeSet<ID>, noun: &str) -> Option<ID> {
+/// Parses a positive count, e.g., the number of commands to undo.
+fn parse_count(token: &str) -> Result<usize, String> {
+    match token.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("That's not a count: {}", token)),
+    }
+}
+
+/// Converts a save name entered by the player into a file name.
+fn save_file(name: &str) -> String {
+    format!("{}.sav", name)
+}
+
+/// Converts a transcript name entered by the player into a file name.
+fn transcript_file(name: &str) -> String {
+    format!("{}.txt", name)
+}
+
+/// Finds the thing in the list that best matches the noun phrase, or None if no
+/// thing matches.  If several things match equally well, asks the player which one
+/// he means: the question is returned as an error, and saved in the world so that
+/// the player's next input can answer it.
+fn find_noun(world: &mut World, ids: BTreeSet<ID>, phrase: &[&str]) -> Result<Option<ID>, String> {
+    let mut best = 0;
+    let mut matches: Vec<ID> = Vec::new();
+
     for id in ids {
-        let thingc = &world.things[&id];
-        if thingc.noun == noun {
-            return Some(id);
+        let score = world.things[&id].score(phrase);
+
+        if score > best {
+            best = score;
+            matches.clear();
+        }
+
+        if score > 0 && score == best {
+            matches.push(id);
         }
     }
 
-    None
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(Some(matches[0])),
+        _ => {
+            let names: Vec<String> = matches
+                .iter()
+                .map(|id| format!("the {}", world.things[id].name))
+                .collect();
+
+            world.question = Some(Question {
+                words: Vec::new(),
+                phrase: phrase.iter().map(|w| w.to_string()).collect(),
+                candidates: matches,
+            });
+
+            Err(format!("Which do you mean, {}?", command::or_list(&names)))
+        }
+    }
 }
 
 //-------------------------------------------------------------------------
 // Command Handler
 
-#[derive(Debug,Clone)]
-enum CommandPattern {
-    /// Match any single arbitrary word
-    Verb(String),
+/// A built-in command's handler, which is given the words that matched its pattern
+/// and the resolved slot arguments.
+type BuiltinHook = fn(&mut Game, &Player, &[&str], &[Arg]) -> StatusResult;
 
-    /// Match any pair of arbitrary words
-    VerbNoun(String,String),
+/// What a command handler does when its pattern matches.
+#[derive(Clone)]
+enum HandlerAction {
+    /// A built-in command, with full access to the game.
+    Builtin(BuiltinHook),
 
-    /// Match any arbitrary word with a visible thing
-    VerbVisible(String),
+    /// A scenario's custom command, which builds a script.
+    Custom(CommandHook),
 }
 
+/// A command: a grammar pattern, and the action to take when the player's input
+/// matches it.
 #[derive(Clone)]
 pub struct CommandHandler {
-    pattern: CommandPattern,
-    hook: CommandHook,
+    pattern: Pattern,
+    action: HandlerAction,
 }
 
 impl CommandHandler {
-    pub fn verb(word: &str, hook: CommandHook) -> Self {
+    /// Creates a built-in command.
+    fn builtin(pattern: &str, hook: BuiltinHook) -> Self {
         Self {
-            pattern: CommandPattern::Verb(word.into()),
-            hook
+            pattern: Pattern::parse(pattern),
+            action: HandlerAction::Builtin(hook),
         }
     }
 
-    pub fn verb_noun(word1: &str, word2: &str, hook: CommandHook) -> Self {
+    /// Creates a custom command with the given pattern, e.g., `wash {visible}`.
+    pub fn custom(pattern: &str, hook: CommandHook) -> Self {
         Self {
-            pattern: CommandPattern::VerbNoun(word1.into(), word2.into()),
-            hook
+            pattern: Pattern::parse(pattern),
+            action: HandlerAction::Custom(hook),
         }
     }
 
-    pub fn verb_visible(word: &str, hook: CommandHook) -> Self {
-        Self {
-            pattern: CommandPattern::VerbVisible(word.into()),
-            hook
-        }
+    /// The command's verb.
+    pub fn verb(&self) -> &str {
+        self.pattern.verb()
     }
 
-    /// Determines whether the words match the command's pattern.
-    fn matches(&self, words: &[&str]) -> bool {
-        match &self.pattern {
-            CommandPattern::Verb(verb) => words.len() == 1 && words[0] == verb,
-            CommandPattern::VerbNoun(verb,noun) => words.len() == 2 && words[0] == verb && words[1] == noun,
-            CommandPattern::VerbVisible(verb) => words.len() == 2 && words[0] == verb,
-        }
+    /// The literal words in the command's pattern.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.pattern.words()
     }
 
-    /// Executes the command
-    fn execute(&self, world: &mut World, player: &Player, words: &[&str]) -> CommandResult {
-        // FIRST, do special checks
-        match &self.pattern {
-            CommandPattern::VerbVisible(_) => {
-                if find_noun(world, phys::visible(world, player.id), words[1]).is_none() {
-                    return Err("You don't see any such thing.".into());
+    /// Executes the command given the words that matched it and the resolved
+    /// arguments.
+    fn execute(
+        &self,
+        game: &mut Game,
+        player: &Player,
+        words: &[&str],
+        args: &[Arg],
+    ) -> StatusResult {
+        // FIRST, the player takes any things he needs to be holding.  If he can't,
+        // the attempt still takes time.
+        let mut took = false;
+
+        for (slot, arg) in self.pattern.slots().zip(args) {
+            if let (Slot::Taken, Arg::Thing(thing)) = (slot, arg) {
+                let world = &mut game.world;
+                if phys::owns(world, player.id, *thing) || world.has_flag(*thing, Immovable) {
+                    continue;
+                }
+
+                match take_first(world, player, *thing) {
+                    Ok(true) => took = true,
+                    Ok(false) => return Ok(Normal(1)),
+                    Err(msg) if took => return failed_after_taking(world, &msg),
+                    Err(msg) => return Err(msg),
                 }
             }
-            _ => ()
         }
 
+        // NEXT, do the command itself.  If the player took something first, the
+        // world has changed even if the command fails.
+        match self.execute_hook(game, player, words, args) {
+            Err(msg) if took => failed_after_taking(&mut game.world, &msg),
+            result => result,
+        }
+    }
+
+    /// Executes the command's hook given the words that matched it and the resolved
+    /// arguments.
+    fn execute_hook(
+        &self,
+        game: &mut Game,
+        player: &Player,
+        words: &[&str],
+        args: &[Arg],
+    ) -> StatusResult {
+        let hook = match &self.action {
+            HandlerAction::Builtin(hook) => return hook(game, player, words, args),
+            HandlerAction::Custom(hook) => hook,
+        };
+
+        // NEXT, the hook sees things by tag.
+        let world = &mut game.world;
+        let args: Vec<String> = args
+            .iter()
+            .map(|arg| match arg {
+                Arg::Thing(id) => world.tag(*id),
+                Arg::Word(word) => word.clone(),
+            })
+            .collect();
+        let args: Vec<&str> = args.iter().map(|s| s.as_ref()).collect();
+
         // NEXT, compute the script, returning any error message
         let script = &mut Script::new();
-        (self.hook)(world, words, script)?;
+        let time = hook(world, &args, script)?;
 
         // NEXT, execute the script
         script.execute(world);
 
-        Ok(())
+        Ok(Normal(time))
+    }
+}
+
+/// Takes the thing as an implicit action.  Returns whether the player can go on
+/// with the command.
+fn take_first(world: &mut World, player: &Player, thing: ID) -> Result<bool, String> {
+    let ack = format!("(first taking the {})", world.things[&thing].name);
+    phys::get_thing(world, player.id, thing, &ack)?;
+
+    Ok(phys::owns(world, player.id, thing))
+}
+
+/// Reports the failure of a command after the player took something for it.  The
+/// take can't be taken back, so the turn counts and the command can be undone.
+fn failed_after_taking(world: &mut World, msg: &str) -> StatusResult {
+    visual::error(world, msg);
+    world.interrupt = true;
+    Ok(Normal(1))
+}
+
+/// Resolves the words in each slot to an argument.  Returns Ok(Err(msg)) if a slot
+/// can't be resolved, and Err(question) if the player needs to say which thing he
+/// meant.
+fn resolve_slots(
+    world: &mut World,
+    player: &Player,
+    slots: &[(Slot, &[&str])],
+) -> Result<Result<Vec<Arg>, String>, String> {
+    let mut args = Vec::new();
+
+    for (slot, phrase) in slots {
+        let scopes = match slot {
+            Slot::Word => {
+                args.push(Arg::Word(phrase[0].into()));
+                continue;
+            }
+            Slot::Visible => vec![phys::visible(world, player.id)],
+            Slot::Taken => vec![
+                phys::contents(world, player.id),
+                phys::visible(world, player.id),
+            ],
+            Slot::Held => vec![
+                phys::droppable(world, player.id),
+                phys::contents(world, player.id),
+            ],
+            Slot::Here => {
+                let held = phys::contents(world, player.id);
+                let immovable = phys::visible(world, player.id)
+                    .into_iter()
+                    .filter(|id| !held.contains(id) && world.has_flag(*id, Immovable))
+                    .collect();
+                vec![phys::takeable(world, player.id), immovable]
+            }
+            Slot::Container => vec![flagged(world, phys::visible(world, player.id), Container)],
+            Slot::Supporter => vec![flagged(world, phys::visible(world, player.id), Supporter)],
+            Slot::Agent => vec![flagged(world, phys::visible(world, player.id), Agent)],
+        };
+
+        // If the phrase is a pronoun, it refers to the thing it was last used for,
+        // provided that thing is still in scope.
+        if let [word] = phrase {
+            if grammar::is_pronoun(word) {
+                match resolve_pronoun(world, player, *slot, word, &scopes) {
+                    Ok(id) => {
+                        args.push(Arg::Thing(id));
+                        continue;
+                    }
+                    Err(msg) => return Ok(Err(msg)),
+                }
+            }
+        }
+
+        // Search each scope in turn, so that the things the command most likely
+        // refers to are preferred.
+        let mut found = None;
+        for ids in scopes {
+            found = find_noun(world, ids, phrase)?;
+            if found.is_some() {
+                break;
+            }
+        }
+
+        match found {
+            Some(id) => args.push(Arg::Thing(id)),
+            None => return Ok(Err(slot_error(world, player, *slot, phrase))),
+        }
+    }
+
+    // NEXT, the direct object, i.e., the first thing, becomes the antecedent of its
+    // pronoun.
+    let object = args.iter().find_map(|arg| match arg {
+        Arg::Thing(id) => Some(*id),
+        Arg::Word(_) => None,
+    });
+
+    if let Some(id) = object {
+        if id != player.id {
+            let pronoun = world.things[&id].pronoun.clone();
+            world.pronouns.insert(pronoun, id);
+        }
+    }
+
+    Ok(Ok(args))
+}
+
+/// Resolves a pronoun to the thing it last referred to, if that thing is in one of
+/// the slot's scopes.
+fn resolve_pronoun(
+    world: &World,
+    player: &Player,
+    slot: Slot,
+    pronoun: &str,
+    scopes: &[BTreeSet<ID>],
+) -> Result<ID, String> {
+    let id = match world.pronouns.get(pronoun) {
+        Some(id) => *id,
+        None => return Err(format!("I'm not sure what \"{}\" refers to.", pronoun)),
+    };
+
+    if scopes.iter().any(|ids| ids.contains(&id)) {
+        Ok(id)
+    } else if phys::visible(world, player.id).contains(&id) {
+        Err(slot_error(world, player, slot, &[world.things[&id].noun()]))
+    } else {
+        Err(format!("You don't see the {} here.", world.things[&id].name))
+    }
+}
+
+/// The things among the IDs that have the flag.
+fn flagged(world: &World, ids: BTreeSet<ID>, flag: Flag) -> BTreeSet<ID> {
+    ids.into_iter().filter(|id| world.has_flag(*id, flag)).collect()
+}
+
+/// The error message for a noun phrase that doesn't resolve to a thing of the
+/// slot's type.
+fn slot_error(world: &World, player: &Player, slot: Slot, phrase: &[&str]) -> String {
+    let named = |ids: BTreeSet<ID>| ids.iter().any(|id| world.things[id].score(phrase) > 0);
+
+    match slot {
+        Slot::Held if named(phys::visible(world, player.id)) => {
+            "You aren't carrying that.".into()
+        }
+        Slot::Here if named(phys::contents(world, player.id)) => "You already have that.".into(),
+        Slot::Container if named(phys::visible(world, player.id)) => {
+            "You can't put things in that.".into()
+        }
+        Slot::Supporter if named(phys::visible(world, player.id)) => {
+            "You can't put things on that.".into()
+        }
+        Slot::Agent if named(phys::visible(world, player.id)) => {
+            "You can only do that to someone.".into()
+        }
+        _ => "You don't see any such thing.".into(),
+    }
+}
+
+/// The direction named by a direction verb.
+fn direction(word: &str) -> Dir {
+    match DIRECTIONS.iter().find(|(verb, _)| *verb == word) {
+        Some((_, dir)) => *dir,
+        None => panic!("Not a direction: {}", word),
+    }
+}
+
+/// Gets the thing argument at the given index.
+fn thing_arg(args: &[Arg], index: usize) -> ID {
+    match &args[index] {
+        Arg::Thing(id) => *id,
+        Arg::Word(_) => panic!("Expected a thing argument: {:?}", args),
+    }
+}
+
+/// Gets the word argument at the given index.
+fn word_arg(args: &[Arg], index: usize) -> &str {
+    match &args[index] {
+        Arg::Word(word) => word,
+        Arg::Thing(_) => panic!("Expected a word argument: {:?}", args),
     }
 }
@@ -13,9 +13,11 @@ pub fn allows(world: &mut World, event: &Event) -> bool {
         let rulec = &world.rules[&id];
         if rulec.is_guard && event == &rulec.event {
             if (rulec.predicate)(world) {
-                // The action is not allowed; execute the script.
+                // The action is not allowed; execute the script, and interrupt
+                // the player.
                 let script = rulec.script.clone();
                 script.execute(world);
+                world.interrupt = true;
                 return false;
             } else {
                 // The action is allowed.
@@ -0,0 +1,408 @@
+//! Save and Restore
+//!
+//! This module writes the mutable parts of the World to a text file, and re-applies
+//! them to a freshly built World.  Only the state that changes during play is saved:
+//! entity locations (including whether they are in or on, and hence inventories),
+//! flag sets (including the rules' Fired flags), the clock, the achievements earned
+//! (and hence the score), and the things the pronouns refer to.  Everything else,
+//! including all of the scenario's hooks, comes from the freshly built scenario.
+//!
+//! The file records the scenario's title and version, and entities are referred to
+//! by tag rather than by ID, so that a file saved from a different scenario is
+//! detected rather than silently misapplied.
+
+use crate::entity::ID;
+use crate::phys;
+use crate::scenario::Scenario;
+use crate::types::Flag;
+use crate::types::Flag::*;
+use crate::types::Placement;
+use crate::world::World;
+use std::fs;
+
+/// The first word of every save file.
+const MAGIC: &str = "bonaventure-save";
+
+/// The save file format version.  Increment this when the format changes.
+///
+/// * Version 2 added the scenario, version, earned, and pronoun lines, "on"
+///   locations, and the flags for containers, doors, locks, and light.
+const VERSION: u32 = 2;
+
+type SaveResult = Result<(), String>;
+
+//--------------------------------------------------------------------------------
+// Public API
+
+/// Saves the world's mutable state to the named file.
+pub fn save(world: &World, scenario: &dyn Scenario, path: &str) -> SaveResult {
+    fs::write(path, to_text(world, scenario))
+        .map_err(|err| format!("Could not save to {}: {}", path, err))
+}
+
+/// Restores the world's mutable state from the named file.  The world should be
+/// freshly built from the scenario.  On error, the world may be partially updated,
+/// and should be discarded.
+pub fn restore(world: &mut World, scenario: &dyn Scenario, path: &str) -> SaveResult {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Could not restore from {}: {}", path, err))?;
+
+    apply(world, scenario, &text)
+}
+
+/// Converts the world's mutable state to save file text.
+pub fn to_text(world: &World, scenario: &dyn Scenario) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    // FIRST, the header.
+    lines.push(format!("{} {}", MAGIC, VERSION));
+    lines.push(format!("scenario {}", scenario.title()));
+    lines.push(format!("version {}", scenario.version()));
+    lines.push(format!("entities {}", world.tags.len()));
+    lines.push(format!("clock {}", world.clock));
+
+    // NEXT, the achievements, in the order earned.
+    for name in &world.earned {
+        lines.push(format!("earned {}", name));
+    }
+
+    // NEXT, the things the pronouns refer to.
+    for (pronoun, id) in &world.pronouns {
+        lines.push(format!("pronoun {} {}", pronoun, world.tag(*id)));
+    }
+
+    // NEXT, the entities, in order of creation.
+    for id in world.tags.keys() {
+        let tag = world.tag(*id);
+
+        if let Some(locc) = world.locations.get(id) {
+            match phys::placement(world, *id) {
+                Placement::In => lines.push(format!("loc {} {}", tag, world.tag(locc.id))),
+                Placement::On => lines.push(format!("loc {} {} on", tag, world.tag(locc.id))),
+            }
+        }
+
+        if let Some(flagc) = world.flag_sets.get(id) {
+            // The set has no natural order; sort so that the file is stable.
+            let mut flags: Vec<String> = flagc.iter().map(|f| flag_text(world, *f)).collect();
+            flags.sort();
+
+            for flag in flags {
+                lines.push(format!("flag {} {}", tag, flag));
+            }
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Applies save file text to a world freshly built from the scenario.
+pub fn apply(world: &mut World, scenario: &dyn Scenario, text: &str) -> SaveResult {
+    let mut lines = text.lines();
+
+    // FIRST, check the header.
+    let header = lines.next().unwrap_or("");
+    match words(header).as_slice() {
+        [MAGIC, version] => match version.parse::<u32>() {
+            Ok(VERSION) => (),
+            Ok(old) if old < VERSION => {
+                return Err(format!(
+                    "That save file is in an old format (version {}), and can't be restored.",
+                    old
+                ));
+            }
+            _ => {
+                return Err(format!(
+                    "Save file version {} is not supported; expected version {}.",
+                    version, VERSION
+                ));
+            }
+        },
+        _ => return Err("Not a Bonaventure save file.".into()),
+    }
+
+    // NEXT, the flags in the file replace the flags in the fresh world.
+    for flagc in world.flag_sets.values_mut() {
+        flagc.set.clear();
+    }
+
+    // NEXT, apply the remaining lines.
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match words(line).as_slice() {
+            ["scenario", title @ ..] => {
+                let title = title.join(" ");
+                if title != words(scenario.title()).join(" ") {
+                    return Err(format!(
+                        "That save file is from a different scenario: {}.",
+                        title
+                    ));
+                }
+            }
+            ["version", version] => {
+                if *version != scenario.version() {
+                    return Err(format!(
+                        "That save file is from version {} of {}; this is version {}.",
+                        version,
+                        scenario.title(),
+                        scenario.version()
+                    ));
+                }
+            }
+            ["entities", count] => {
+                if *count != world.tags.len().to_string() {
+                    return Err(mismatch());
+                }
+            }
+            ["clock", clock] => {
+                world.clock = clock
+                    .parse()
+                    .map_err(|_| format!("Invalid clock in save file: {}", clock))?;
+            }
+            ["earned", name @ ..] if !name.is_empty() => {
+                let name = name.join(" ");
+                if !world.achievements.iter().any(|a| a.name == name) {
+                    return Err(mismatch());
+                }
+                world.award(&name);
+            }
+            ["pronoun", pronoun, thing] => {
+                let id = lookup(world, thing)?;
+
+                if !world.is_thing(id) {
+                    return Err(mismatch());
+                }
+                world.pronouns.insert(pronoun.to_string(), id);
+            }
+            ["loc", thing, container] => {
+                let tid = lookup(world, thing)?;
+                let cid = lookup(world, container)?;
+
+                if !world.has_location(tid) || !world.has_inventory(cid) {
+                    return Err(mismatch());
+                }
+                phys::put_in(world, tid, cid);
+            }
+            ["loc", thing, supporter, "on"] => {
+                let tid = lookup(world, thing)?;
+                let sid = lookup(world, supporter)?;
+
+                if !world.has_location(tid) || !world.has_flag(sid, Supporter) {
+                    return Err(mismatch());
+                }
+                phys::put_on(world, tid, sid);
+            }
+            ["flag", tag, rest @ ..] if !rest.is_empty() => {
+                let id = lookup(world, tag)?;
+                let flag = parse_flag(world, rest)?;
+
+                if !world.has_flags(id) {
+                    return Err(mismatch());
+                }
+                world.set_flag(id, flag);
+            }
+            _ => return Err(format!("Invalid line in save file: {}", line)),
+        }
+    }
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------
+// Helpers
+
+/// The standard error for a save file from some other scenario.
+fn mismatch() -> String {
+    "That save file is from a different scenario.".into()
+}
+
+/// Splits a line into whitespace-delimited words.
+fn words(line: &str) -> Vec<&str> {
+    line.split_whitespace().collect()
+}
+
+/// Looks up a tag from the save file, returning an error if it doesn't exist.
+fn lookup(world: &World, tag: &str) -> Result<ID, String> {
+    world.lookup_id(tag).ok_or_else(mismatch)
+}
+
+/// Converts a flag to its save file representation.  User flag names come
+/// last, so that they may contain whitespace.
+fn flag_text(world: &World, flag: Flag) -> String {
+    match flag {
+        FireOnce => "FireOnce".into(),
+        Fired => "Fired".into(),
+        Dead => "Dead".into(),
+        Seen(id) => format!("Seen {}", world.tag(id)),
+        Immovable => "Immovable".into(),
+        Scenery => "Scenery".into(),
+        Container => "Container".into(),
+        Supporter => "Supporter".into(),
+        Openable => "Openable".into(),
+        Open => "Open".into(),
+        Lockable => "Lockable".into(),
+        Locked => "Locked".into(),
+        Dark => "Dark".into(),
+        LightSource => "LightSource".into(),
+        Lit => "Lit".into(),
+        Agent => "Agent".into(),
+        User(name) => format!("User {}", name),
+        UserId(name, id) => format!("UserId {} {}", world.tag(id), name),
+    }
+}
+
+/// Parses a flag from its save file representation.
+fn parse_flag(world: &World, words: &[&str]) -> Result<Flag, String> {
+    let flag = match words {
+        ["FireOnce"] => FireOnce,
+        ["Fired"] => Fired,
+        ["Dead"] => Dead,
+        ["Seen", tag] => Seen(lookup(world, tag)?),
+        ["Immovable"] => Immovable,
+        ["Scenery"] => Scenery,
+        ["Container"] => Container,
+        ["Supporter"] => Supporter,
+        ["Openable"] => Openable,
+        ["Open"] => Open,
+        ["Lockable"] => Lockable,
+        ["Locked"] => Locked,
+        ["Dark"] => Dark,
+        ["LightSource"] => LightSource,
+        ["Lit"] => Lit,
+        ["Agent"] => Agent,
+        ["User", name @ ..] if !name.is_empty() => User(intern(world, &name.join(" "))),
+        ["UserId", tag, name @ ..] if !name.is_empty() => {
+            UserId(intern(world, &name.join(" ")), lookup(world, tag)?)
+        }
+        _ => return Err(format!("Invalid flag in save file: {}", words.join(" "))),
+    };
+
+    Ok(flag)
+}
+
+/// User flags are named by static strings, usually constants defined by the
+/// scenario.  Returns the matching name from the world's rules and flag sets
+/// if there is one; otherwise the name is leaked, which is bounded by the size
+/// of the save file.
+fn intern(world: &World, name: &str) -> &'static str {
+    let known = world
+        .flag_sets
+        .values()
+        .flat_map(|flagc| flagc.iter())
+        .chain(world.rules.values().flat_map(|rulec| rulec.script.flags()));
+
+    for flag in known {
+        match flag {
+            User(known) | UserId(known, _) if *known == name => return known,
+            _ => (),
+        }
+    }
+
+    Box::leak(name.to_string().into_boxed_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldQuery;
+    use crate::world_builder::WorldBuilder;
+
+    const DIRTY: Flag = User("DIRTY");
+
+    struct TestScenario {
+        version: &'static str,
+    }
+
+    impl Scenario for TestScenario {
+        fn title(&self) -> &str {
+            "Save Test"
+        }
+
+        fn version(&self) -> &str {
+            self.version
+        }
+
+        fn intro(&self) -> &str {
+            ""
+        }
+
+        fn build(&self) -> World {
+            let mut wb = WorldBuilder::new();
+            wb.player().location("room");
+            wb.room("room", "Room");
+            wb.thing("note", "note", "note").location("room");
+            wb.thing("table", "table", "table").location("room").supporter();
+            wb.thing("cup", "cup", "cup").location("room");
+            wb.thing("hands", "hands", "hands").location("PLAYER").flag(DIRTY);
+            wb.achievement("took the note", 5);
+            wb.achievement("washed up", 2);
+            wb.world()
+        }
+    }
+
+    const V1: TestScenario = TestScenario { version: "1.0" };
+
+    #[test]
+    fn round_trip() {
+        let mut world = V1.build();
+        let pid = world.pid;
+        let note = world.lookup("note");
+        world.clock = 7;
+        phys::put_in(&mut world, note, pid);
+        let (cup, table) = (world.lookup("cup"), world.lookup("table"));
+        phys::put_on(&mut world, cup, table);
+        world.set_flag(note, DIRTY);
+        world.unset_flag(world.lookup("hands"), DIRTY);
+        world.award("washed up");
+        world.pronouns.insert("it".into(), note);
+
+        let mut restored = V1.build();
+        apply(&mut restored, &V1, &to_text(&world, &V1)).unwrap();
+
+        assert_eq!(restored.clock, 7);
+        assert!(restored.owns("PLAYER", "note"));
+        assert!(!restored.owns("room", "note"));
+        assert!(restored.owns("table", "cup"));
+        assert_eq!(phys::placement(&restored, cup), Placement::On);
+        assert!(restored.has("note", DIRTY));
+        assert!(!restored.has("hands", DIRTY));
+        assert_eq!(restored.score, 2);
+        assert!(restored.has_earned("washed up"));
+        assert_eq!(restored.pronouns.get("it"), Some(&note));
+        assert!(!restored.has_earned("took the note"));
+        assert_eq!(to_text(&restored, &V1), to_text(&world, &V1));
+    }
+
+    #[test]
+    fn bad_header() {
+        let mut world = V1.build();
+        assert!(apply(&mut world, &V1, "not a save file\n").is_err());
+        assert!(apply(&mut world, &V1, "bonaventure-save 999\n").is_err());
+
+        let result = apply(&mut world, &V1, "bonaventure-save 1\nclock 3\n");
+        assert!(result.unwrap_err().contains("old format"));
+    }
+
+    #[test]
+    fn wrong_version() {
+        let v2 = TestScenario { version: "2.0" };
+        let text = to_text(&V1.build(), &V1);
+        let result = apply(&mut v2.build(), &v2, &text);
+        assert!(result.unwrap_err().contains("version 1.0"));
+    }
+
+    #[test]
+    fn wrong_scenario() {
+        let mut world = V1.build();
+        let text = "bonaventure-save 2\nloc lamp PLAYER\n";
+        assert_eq!(apply(&mut world, &V1, text), Err(mismatch()));
+
+        let text = "bonaventure-save 2\nscenario Other Game\n";
+        assert!(apply(&mut world, &V1, text).unwrap_err().contains("Other Game"));
+    }
+}
@@ -3,6 +3,7 @@
 use crate::phys;
 use self::Action::*;
 use crate::types::Flag;
+use crate::types::GameStatus;
 use crate::visual;
 use crate::world::World;
 use crate::world_builder;
@@ -35,6 +36,15 @@ enum Action {
 
     /// Revive(player): Revive the tagged player/NPC (currently, only the player)
     Revive(String),
+
+    /// Win: The player has won the game.
+    Win,
+
+    /// Lose: The player has lost the game.
+    Lose,
+
+    /// Award(name): Award the named achievement's points, if not already earned.
+    Award(String),
 }
 
 /// A script of actions for execution.  Scripts can be pre-defined and executed
@@ -52,20 +62,28 @@ impl Script {
         }
     }
 
-    /// Dumps the script.  Each line is preceded by the leader.
-    pub fn dump(&self, leader: &str) {
+    /// Dumps the script to the world's output.  Each line is preceded by the leader.
+    pub fn dump(&self, world: &World, leader: &str) {
         for action in &self.actions {
-            println!("{}Action: {:?}", leader, action);
+            visual::debug(world, &format!("{}Action: {:?}", leader, action));
         }
     }
 
+    /// Returns the flags set or unset by the script.
+    pub fn flags(&self) -> impl Iterator<Item = &Flag> {
+        self.actions.iter().filter_map(|action| match action {
+            SetFlag(_, flag) | UnsetFlag(_, flag) => Some(flag),
+            _ => None,
+        })
+    }
+
     /// Executes a script on the world.
     pub fn execute(&self, world: &mut World) {
         for action in &self.actions {
             match action {
                 // Print the rule's visual
                 Print(visual) => {
-                    visual::info(&visual);
+                    visual::info(world, &visual);
                 }
 
                 // Set the flag on the entity's flag set
@@ -101,13 +119,35 @@ impl Script {
                 // Kill the player/NPC
                 Kill(player) => {
                     world.set_flag(world.lookup(player), Flag::Dead);
-                    visual::act("*** You have died. ***");
+                    world.interrupt = true;
+                    visual::act(world, "*** You have died. ***");
                 }
 
                 // Revive the player/NPC
                 Revive(player) => {
                     world.unset_flag(world.lookup(player), Flag::Dead);
-                    visual::act("*** You are alive! ***");
+                    visual::act(world, "*** You are alive! ***");
+                }
+
+                // End the game.  The Game announces the ending at the end of the turn.
+                Win => {
+                    world.status = GameStatus::Won;
+                }
+
+                Lose => {
+                    world.status = GameStatus::Lost;
+                }
+
+                // Award points for an achievement
+                Award(name) => {
+                    match world.award(name) {
+                        0 => (),
+                        1 => visual::info(world, "[Your score has gone up by 1 point.]"),
+                        points => visual::info(
+                            world,
+                            &format!("[Your score has gone up by {} points.]", points),
+                        ),
+                    }
                 }
             }
         }
@@ -153,4 +193,20 @@ impl Script {
     pub fn revive(&mut self, player: &str) {
         self.add(Action::Revive(player.into()));
     }
+
+    /// Adds an action to award the named achievement, which the scenario must have
+    /// declared.  The points are awarded only once.
+    pub fn award(&mut self, name: &str) {
+        self.add(Action::Award(name.into()));
+    }
+
+    /// Adds an action to end the game with a win.
+    pub fn win(&mut self) {
+        self.add(Action::Win);
+    }
+
+    /// Adds an action to end the game with a loss.
+    pub fn lose(&mut self) {
+        self.add(Action::Lose);
+    }
 }
@@ -0,0 +1,126 @@
+//! # Transcripts
+//! A Transcript sits in the game's output path.  It passes every paragraph through to
+//! the real output, and while recording it also copies the paragraphs, along with the
+//! player's input, to a text file.  Thus the file shows exactly what the player saw,
+//! including rule text, errors, and debugging output.
+
+use crate::conmark::confmt;
+use crate::output::Output;
+use crate::output::OutputHandle;
+use crate::output::ParaKind;
+use crate::scenario::Scenario;
+use std::fs::File;
+use std::io::Write;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// An Output that records game output to a file, when asked to.
+pub struct Transcript {
+    // The output to which paragraphs are passed through.
+    output: OutputHandle,
+
+    // The file being recorded to, if any.
+    file: Option<File>,
+}
+
+impl Transcript {
+    /// Creates a Transcript that passes paragraphs through to the given output.  It
+    /// doesn't record until started.
+    pub fn new(output: OutputHandle) -> Self {
+        Self { output, file: None }
+    }
+
+    /// Is the transcript being recorded?
+    pub fn is_recording(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Starts recording to the named file, replacing any existing file.  The file
+    /// begins with a header giving the scenario's title and version, and the date.
+    pub fn start(&mut self, path: &str, scenario: &dyn Scenario) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|err| format!("Could not record to {}: {}", path, err))?;
+
+        self.file = Some(file);
+        self.write(&format!(
+            "{}, version {}\nTranscript recorded {}\n\n",
+            scenario.title(),
+            scenario.version(),
+            today()
+        ));
+        Ok(())
+    }
+
+    /// Stops recording.
+    pub fn stop(&mut self) {
+        self.file = None;
+    }
+
+    /// Records the player's input.
+    pub fn input(&mut self, input: &str) {
+        self.write(&format!("> {}\n", input));
+    }
+
+    /// Writes text to the file, if recording.  If the file can't be written, recording
+    /// stops.
+    fn write(&mut self, text: &str) {
+        if let Some(file) = &mut self.file {
+            if file.write_all(text.as_bytes()).is_err() {
+                self.file = None;
+            }
+        }
+    }
+}
+
+impl Output for Transcript {
+    fn para(&mut self, kind: ParaKind, text: &str) {
+        self.output.borrow_mut().para(kind, text);
+
+        // Paragraphs are recorded unwrapped, laid out as on the terminal.
+        match kind {
+            ParaKind::RoomName => self.write(&format!("{}\n", confmt(text))),
+            ParaKind::Debug => self.write(&format!("{}\n", text)),
+            _ => self.write(&format!("{}\n\n", confmt(text))),
+        }
+    }
+}
+
+/// Today's date, as YYYY-MM-DD (UTC).
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs / 86_400);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts a count of days since 1970-01-01 into a (year, month, day) date in the
+/// proleptic Gregorian calendar.  See Howard Hinnant's "chrono-Compatible Low-Level
+/// Date Algorithms".
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_743), (2026, 10, 17));
+    }
+}
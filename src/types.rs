@@ -15,12 +15,15 @@ pub type RulePredicate = &'static Fn(&WorldQuery) -> bool;
 /// receive the string.
 pub type EntityProseHook = &'static Fn(&WorldQuery, &str, &mut ProseBuffer);
 
-/// The normal command handler result.
-pub type CommandResult = Result<(), String>;
+/// The normal command handler result: the number of turns the command took,
+/// or an error message.
+pub type CommandResult = Result<Time, String>;
 
 /// A closure that determines whether a command is valid, then builds a script
-/// to execute it.  The hook may query the world and the command words, and
-/// return an Err(String) if there's a problem, and Ok(()) otherwise.
+/// to execute it.  The hook may query the world and the command's arguments: the
+/// tags of the things and the words that fill the command pattern's slots.  It may
+/// return an Err(String) if there's a problem, and Ok(time) otherwise, where time
+/// is the number of turns the command takes, usually 1.
 pub type CommandHook = &'static Fn(&WorldQuery, &[&str], &mut Script) -> CommandResult;
 
 /// The time, in game turns
@@ -28,18 +31,41 @@ pub type Time = usize;
 
 /// Directions
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum Dir {
     North,
     South,
     East,
     West,
+    Northeast,
+    Northwest,
+    Southeast,
+    Southwest,
     Up,
     Down,
     In,
     Out,
 }
 
+impl Dir {
+    /// The opposite direction, i.e., the way back.
+    pub fn opposite(self) -> Dir {
+        match self {
+            Dir::North => Dir::South,
+            Dir::South => Dir::North,
+            Dir::East => Dir::West,
+            Dir::West => Dir::East,
+            Dir::Northeast => Dir::Southwest,
+            Dir::Northwest => Dir::Southeast,
+            Dir::Southeast => Dir::Northwest,
+            Dir::Southwest => Dir::Northeast,
+            Dir::Up => Dir::Down,
+            Dir::Down => Dir::Up,
+            Dir::In => Dir::Out,
+            Dir::Out => Dir::In,
+        }
+    }
+}
+
 /// The different kinds of prose supported by an entity.
 #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub enum ProseType {
@@ -83,6 +109,38 @@ pub enum Flag {
     /// should also be Immovable.
     Scenery,
 
+    /// Is the thing a container, i.e., can things be put in it?
+    Container,
+
+    /// Is the thing a supporter, i.e., can things be put on it?
+    Supporter,
+
+    /// Can the thing be opened and closed?
+    Openable,
+
+    /// Is the (openable) thing open?  A closed container hides its contents.
+    Open,
+
+    /// Can the thing be locked and unlocked with its key?
+    Lockable,
+
+    /// Is the (lockable) thing locked?  A locked thing can't be opened.
+    Locked,
+
+    /// Is the room dark?  The player can't see in a dark room unless there's a lit
+    /// light source in it, or he's carrying one.
+    Dark,
+
+    /// Can the thing be lit and extinguished, to light up dark rooms?
+    LightSource,
+
+    /// Is the (light source) thing lit?
+    Lit,
+
+    /// Is the thing an agent, i.e., a character the player can give things to,
+    /// talk to, or fight?
+    Agent,
+
     /// A generic flag type for use by users
     User(&'static str),
 
@@ -105,6 +163,91 @@ pub enum Event {
     /// ReadThing(player, thing): A player has read (or wants to read) a thing's
     /// Book prose.
     ReadThing(ID, ID),
+
+    /// PutIn(player, thing, container): A player has put (or wants to put) a thing
+    /// in a container.
+    PutIn(ID, ID, ID),
+
+    /// PutOn(player, thing, supporter): A player has put (or wants to put) a thing
+    /// on a supporter.
+    PutOn(ID, ID, ID),
+
+    /// OpenThing(player, thing): A player has opened (or wants to open) a thing
+    OpenThing(ID, ID),
+
+    /// CloseThing(player, thing): A player has closed (or wants to close) a thing
+    CloseThing(ID, ID),
+
+    /// LockThing(player, thing): A player has locked (or wants to lock) a thing with
+    /// its key
+    LockThing(ID, ID),
+
+    /// UnlockThing(player, thing): A player has unlocked (or wants to unlock) a thing with
+    /// its key
+    UnlockThing(ID, ID),
+
+    /// MoveInDark(player, room): A player has moved (or wants to move) out of a room
+    /// while it's dark.
+    MoveInDark(ID, ID),
+}
+
+/// How a thing is placed in its location: in it, or on top of it.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Placement {
+    In,
+    On,
+}
+
+/// Something the player can achieve, for points.
+#[derive(Clone, Debug)]
+pub struct Achievement {
+    /// The achievement's name, e.g., "took the sword".
+    pub name: String,
+
+    /// The number of points it's worth.
+    pub points: usize,
+}
+
+/// A question the game has asked the player about his last command, which his next
+/// input may answer.  At present, the only question is which of several things he
+/// meant.
+#[derive(Clone, Debug)]
+pub struct Question {
+    /// The words of the command that raised the question.
+    pub words: Vec<String>,
+
+    /// The noun phrase that matched more than one thing.
+    pub phrase: Vec<String>,
+
+    /// The things it matched.
+    pub candidates: Vec<ID>,
+}
+
+/// A word in the player's last command that the game didn't know, which an "oops"
+/// command can correct.
+#[derive(Clone, Debug)]
+pub struct Misspelling {
+    /// The words of the command.
+    pub words: Vec<String>,
+
+    /// The index of the unknown word.
+    pub index: usize,
+}
+
+/// The status of the game as a whole.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum GameStatus {
+    /// The game is in progress.
+    Playing,
+
+    /// The player has won the game.
+    Won,
+
+    /// The player has lost the game, e.g., by dying.
+    Lost,
+
+    /// The player has quit the game.
+    Quit,
 }
 
 /// The destination of a link.
@@ -113,6 +256,10 @@ pub enum LinkDest {
     /// The link goes to another room.
     Room(ID),
 
+    /// The link goes through a door to the room on its other side.  Both rooms link
+    /// to the same door, so they see it in the same state.
+    Door(ID),
+
     /// The link is a dead end.  The string is the prose to display to
     /// the user.
     DeadEnd(String)
@@ -1,17 +1,19 @@
 //! # Visual system
 //
 // This module centralizes all of the code that turns game entities into prose for display
-// to the user.  It depends on the output routines from the `console` module, and
-// indirectly on the formatting and wrapping routines in the `conmark` module.
+// to the user.  All output goes to the world's Output, tagged with the kind of paragraph;
+// see the `output` module.
 //
 // In a normal ECS architecture, the visual system is called in the game loop after the
 // physics system to render the current scene.  In a text adventure, text is displayed at
 // appropriate moments in processing; thus, this module is called as needed, rather than
 // doing its work all at once.
 
-use crate::console::para;
 use crate::entity::ID;
+use crate::output::ParaKind;
 use crate::phys;
+use crate::types::Flag::*;
+use crate::types::Placement;
 use crate::types::ProseType;
 use crate::types::ProseBuffer;
 use crate::world::World;
@@ -29,23 +31,25 @@ enum Detail {
 
 //-----------------------------------------------------------------------------
 // Basic Messages
-//
-// At present these are all treated like "para"; but this gives the opportunity
-// to distinguish them at some future time.
 
 /// Outputs a player action, e.g., "Taken."
-pub fn act(msg: &str) {
-    para(msg);
+pub fn act(world: &World, msg: &str) {
+    para(world, ParaKind::Act, msg);
 }
 
 /// Outputs an error message.
-pub fn error(msg: &str) {
-    para(msg);
+pub fn error(world: &World, msg: &str) {
+    para(world, ParaKind::Error, msg);
 }
 
 /// Outputs information (e.g., help)
-pub fn info(msg: &str) {
-    para(msg);
+pub fn info(world: &World, msg: &str) {
+    para(world, ParaKind::Info, msg);
+}
+
+/// Outputs a line of debugging output.
+pub fn debug(world: &World, msg: &str) {
+    para(world, ParaKind::Debug, msg);
 }
 
 //-----------------------------------------------------------------------------
@@ -70,23 +74,29 @@ pub fn room_brief(world: &World, id: ID) {
 /// * A full description includes the room's name, visual, and any things that are present.
 /// * A brief description omits the visual; it's used for rooms that the player has visited
 ///   before.
+/// * If the room is dark, the player sees nothing at all.
 fn print_room(world: &World, id: ID, detail: Detail) {
     let roomc = &world.rooms[&id];
 
+    if phys::is_dark(world, id) {
+        para(world, ParaKind::RoomBody, "It is pitch dark.");
+        return;
+    }
+
     // FIRST, display the room's description
     if detail == Detail::Full {
+        para(world, ParaKind::RoomName, &roomc.name);
+
         let mut buff = ProseBuffer::new();
-        buff.puts(&roomc.name);
-        buff.newline();
         buff.puts(&get_prose(world, id, ProseType::Room));
-        for sid in phys::scenery(world, id) {
+        for sid in phys::scenery(world, id).into_iter().chain(phys::doors(world, id)) {
             if world.has_prose_type(sid, ProseType::Scenery) {
                 buff.puts(&get_prose(world, sid, ProseType::Scenery));
             }
         }
-        para(&buff.get());
+        para(world, ParaKind::RoomBody, &buff.get());
     } else {
-        para(&roomc.name);
+        para(world, ParaKind::RoomBrief, &roomc.name);
     }
 
     // NEXT, list any "removable" objects in the room's inventory.  (We don't list
@@ -94,8 +104,11 @@ fn print_room(world: &World, id: ID, detail: Detail) {
     let list = invent_list(world, &phys::non_scenery(world, id));
 
     if !list.is_empty() {
-        para!("You see: {}.", list);
+        para(world, ParaKind::RoomContents, &format!("You see: {}.", list));
     }
+
+    // NEXT, list what's in or on the things in the room, scenery included.
+    print_contents(world, ParaKind::RoomContents, &phys::contents(world, id));
 }
 
 //-----------------------------------------------------------------------------
@@ -104,10 +117,45 @@ fn print_room(world: &World, id: ID, detail: Detail) {
 /// Outputs a description of a thing.
 pub fn thing(world: &World, id: ID) {
     // FIRST, display the thing's description
-    para(&get_prose(world, id, ProseType::Thing));
+    para(world, ParaKind::Thing, &get_prose(world, id, ProseType::Thing));
+
+    // NEXT, say whether it's open or closed.
+    if world.has_flag(id, Openable) {
+        let state = if world.has_flag(id, Open) { "open" } else { "closed" };
+        para(world, ParaKind::Thing, &format!("The {} is {}.", world.things[&id].name, state));
+    }
+
+    // NEXT, list what's in or on it.
+    let mut ids = BTreeSet::new();
+    ids.insert(id);
+    print_contents(world, ParaKind::Thing, &ids);
+}
+
+/// Outputs what's in or on each of the things that shows its contents, e.g.,
+/// "In the box: pen, pencil.", and then what's in or on those things, and so on.
+/// Things with nothing in or on them are skipped.
+fn print_contents(world: &World, kind: ParaKind, ids: &BTreeSet<ID>) {
+    for id in ids {
+        if !phys::shows_contents(world, *id) {
+            continue;
+        }
 
-    // TODO: eventually we will want to describe its contents, if it has
-    // contents, or other changeable state.
+        // A thing can be both a container and a supporter, e.g., a desk, so list
+        // what's in it and what's on it separately.
+        for (placement, prep) in &[(Placement::In, "In"), (Placement::On, "On")] {
+            let contents: BTreeSet<ID> = phys::non_scenery(world, *id)
+                .into_iter()
+                .filter(|thing| phys::placement(world, *thing) == *placement)
+                .collect();
+
+            if !contents.is_empty() {
+                let list = invent_list(world, &contents);
+                para(world, kind, &format!("{} the {}: {}.", prep, world.things[id].name, list));
+
+                print_contents(world, kind, &contents);
+            }
+        }
+    }
 }
 
 /// Can this be read as a book?
@@ -119,10 +167,10 @@ pub fn can_read(world: &World, thing: ID) -> bool {
 pub fn read(world: &World, book: ID) {
     let mut buff = ProseBuffer::new();
     buff.puts("The");
-    buff.puts(&world.things[&book].noun);
+    buff.puts(world.things[&book].noun());
     buff.puts("reads:");
     buff.puts(&get_prose(world, book, ProseType::Book));
-    act(&buff.get());
+    act(world, &buff.get());
 }
 
 //-----------------------------------------------------------------------------
@@ -142,7 +190,7 @@ pub fn player(world: &World, pid: ID) {
             }
         }
     }
-    para(&buff.get());
+    para(world, ParaKind::Thing, &buff.get());
 
     // TODO: Could add inventory.
 }
@@ -154,9 +202,27 @@ pub fn player_inventory(world: &World, pid: ID) {
     let ids = phys::droppable(world, pid);
 
     if ids.is_empty() {
-        para("You aren't carrying anything.");
+        para(world, ParaKind::Inventory, "You aren't carrying anything.");
     } else {
-        para!("You have: {}.\n", invent_list(world, &ids));
+        let list = invent_list(world, &ids);
+        para(world, ParaKind::Inventory, &format!("You have: {}.", list));
+        print_contents(world, ParaKind::Inventory, &ids);
+    }
+
+    // NEXT, show the player's load, if he's limited in what he can carry.
+    let invc = &world.inventories[&pid];
+    let mut limits: Vec<String> = Vec::new();
+
+    if let Some(max) = invc.max_weight {
+        limits.push(format!("weight {} of {}", phys::load_weight(world, pid), max));
+    }
+
+    if let Some(max) = invc.max_size {
+        limits.push(format!("bulk {} of {}", phys::load_size(world, pid), max));
+    }
+
+    if !limits.is_empty() {
+        para(world, ParaKind::Inventory, &format!("Your load: {}.", limits.join(", ")));
     }
 }
 
@@ -179,6 +245,11 @@ fn invent_list(world: &World, ids: &BTreeSet<ID>) -> String {
 //-----------------------------------------------------------------------------
 // Helpers
 
+/// Outputs a paragraph of the given kind to the world's output.
+fn para(world: &World, kind: ParaKind, text: &str) {
+    world.output.borrow_mut().para(kind, text);
+}
+
 /// Get the specific type of prose from the entity
 pub fn get_prose(world: &World, id: ID, prose_type: ProseType) -> String {
     assert!(world.has_prose(id), "Not prose: [{}]", id);
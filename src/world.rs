@@ -1,5 +1,8 @@
 //! The game world
+use crate::console::TerminalOutput;
+use crate::player_control;
 use crate::player_control::CommandHandler;
+use crate::entity::door_component::*;
 use crate::entity::flag_set_component::*;
 use crate::entity::inventory_component::*;
 use crate::entity::location_component::*;
@@ -10,10 +13,16 @@ use crate::entity::rule_component::*;
 use crate::entity::tag_component::*;
 use crate::entity::thing_component::*;
 use crate::entity::ID;
+use crate::grammar;
+use crate::phys;
+use crate::output::OutputHandle;
 use crate::types::*;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::rc::Rc;
 
 pub const LIMBO: ID = 0;
 
@@ -22,7 +31,7 @@ pub const LIMBO: ID = 0;
 /// constitute a low-level interface for interacting with the world; e.g., `set_location()`
 /// will set the player's location, but that's all it does.  The game logic for entering a new
 /// room should be implemented elsewhere.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct World {
     //--------------------------------------------------------------------------------------------
     // World-Global Data
@@ -38,6 +47,28 @@ pub struct World {
     // The game clock
     pub clock: Time,
 
+    // Whether the game is still in progress, and if not, how it ended.
+    pub status: GameStatus,
+
+    // The player's score.
+    pub score: usize,
+
+    // The names of the achievements the player has earned, in the order earned.
+    pub earned: Vec<String>,
+
+    // The question the player was asked about his last command, if any.
+    pub question: Option<Question>,
+
+    // The unknown word in the player's last command, if any.
+    pub misspelling: Option<Misspelling>,
+
+    // The thing most recently referred to for each pronoun, e.g., "it" or "them".
+    pub pronouns: BTreeMap<String, ID>,
+
+    // Set when something happens during a command that should stop the player's
+    // remaining queued commands, e.g., a guard denying an action, or death.
+    pub interrupt: bool,
+
     //--------------------------------------------------------------------------------------------
     // Entity Components
     /// Tag Components: Identifiers for the entities.  This is a BTreeMap so that we can
@@ -67,6 +98,9 @@ pub struct World {
     /// Thing Components: Information about things that the player can interact with.
     pub things: HashMap<ID, ThingComponent>,
 
+    /// Door Components: Information about doors, things that connect two rooms.
+    pub doors: HashMap<ID, DoorComponent>,
+
     /// Rule Components: Rules that can fire.  We use BTreeMap to ensure that rules fire
     /// in order of definition.
     pub rules: BTreeMap<ID, RuleComponent>,
@@ -81,6 +115,28 @@ pub struct World {
 
     // Mapping from verb synonyms to verbs
     pub synonyms: HashMap<String, String>,
+
+    // Mapping from multi-word verb phrases, e.g., "pick up", to verbs, longest first.
+    pub phrases: Vec<(Vec<String>, String)>,
+
+    // The achievements the scenario has declared, in order of declaration.
+    pub achievements: Vec<Achievement>,
+
+    // The maximum possible score, i.e., the total points of all achievements.
+    pub max_score: usize,
+
+    // Whether the player may undo commands.  Some scenarios make combat or death
+    // permanent.
+    pub allow_undo: bool,
+
+    // Where game output goes.  By default, it goes to the terminal.
+    pub output: OutputHandle,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl World {
@@ -94,6 +150,13 @@ impl World {
             tag_map: HashMap::new(),
             pid: 0,
             clock: 0,
+            status: GameStatus::Playing,
+            score: 0,
+            earned: Vec::new(),
+            question: None,
+            misspelling: None,
+            interrupt: false,
+            pronouns: BTreeMap::new(),
             tags: BTreeMap::new(),
             flag_sets: HashMap::new(),
             inventories: HashMap::new(),
@@ -102,27 +165,46 @@ impl World {
             players: HashMap::new(),
             rooms: HashMap::new(),
             things: HashMap::new(),
+            doors: HashMap::new(),
             rules: BTreeMap::new(),
-            command_handlers: Vec::new(),
+            command_handlers: player_control::builtin_commands(),
             verbs: HashSet::new(),
             synonyms: HashMap::new(),
+            phrases: Vec::new(),
+            achievements: Vec::new(),
+            max_score: 0,
+            allow_undo: true,
+            output: Rc::new(RefCell::new(TerminalOutput)),
         };
 
         // NEXT, add the standard verbs and synonyms
         // TODO: Decide where this should go.  Possibly not here.
         world.add_verb("go");
 
-        world.add_verb("north");
-        world.add_syn("north", "n");
-
-        world.add_verb("south");
-        world.add_syn("south", "s");
+        for (dir, abbrev) in &[
+            ("north", "n"),
+            ("south", "s"),
+            ("east", "e"),
+            ("west", "w"),
+            ("northeast", "ne"),
+            ("northwest", "nw"),
+            ("southeast", "se"),
+            ("southwest", "sw"),
+            ("up", "u"),
+            ("down", "d"),
+        ] {
+            world.add_verb(dir);
+            world.add_syn(dir, abbrev);
+            world.add_phrase(dir, &format!("go {}", dir));
+        }
 
-        world.add_verb("east");
-        world.add_syn("east", "e");
+        world.add_verb("in");
+        world.add_syn("in", "enter");
+        world.add_phrase("in", "go in");
 
-        world.add_verb("west");
-        world.add_syn("west", "w");
+        world.add_verb("out");
+        world.add_syn("out", "exit");
+        world.add_phrase("out", "go out");
 
         world.add_verb("help");
         world.add_verb("look");
@@ -133,29 +215,55 @@ impl World {
 
         world.add_verb("examine");
         world.add_syn("examine", "x");
+        world.add_phrase("examine", "look at");
 
         world.add_verb("get");
         world.add_syn("get", "take");
+        world.add_phrase("get", "pick up");
 
         world.add_verb("drop");
+        world.add_phrase("drop", "put down");
+
+        world.add_verb("put");
+        world.add_syn("in", "into");
+        world.add_syn("on", "onto");
+
+        world.add_syn("all", "everything");
+        world.add_syn("except", "but");
 
         world.add_verb("read");
 
+        world.add_verb("open");
+        world.add_verb("close");
+        world.add_syn("close", "shut");
+        world.add_verb("lock");
+        world.add_verb("unlock");
+
+        world.add_verb("light");
+        world.add_phrase("light", "turn on");
+        world.add_verb("extinguish");
+        world.add_syn("extinguish", "douse");
+        world.add_phrase("extinguish", "turn off");
+
         world.add_verb("restart");
         world.add_verb("undo");
+        world.add_verb("redo");
+        world.add_verb("save");
+        world.add_verb("restore");
+        world.add_verb("score");
+        world.add_verb("full");
+        world.add_verb("script");
+        world.add_syn("script", "transcript");
+        world.add_verb("oops");
+        world.add_verb("again");
+        world.add_syn("again", "g");
         world.add_verb("quit");
-        world.add_syn("quit", "exit");
         world.add_syn("quit", "bye");
 
         // NEXT, add debugging-only verbs
         world.add_verb("list");
         world.add_verb("dump");
 
-        // NEXT, add custom verbs
-        // TODO: Should be part of scenario, once the scenario can define
-        // custom command handlers.
-        world.add_verb("wash");
-
         world
     }
 
@@ -233,6 +341,11 @@ impl World {
         self.things.get(&id).is_some() && self.has_location(id) && self.has_flags(id)
     }
 
+    /// Is this entity a door, i.e., a thing that connects two rooms?
+    pub fn is_door(&self, id: ID) -> bool {
+        self.doors.contains_key(&id) && self.is_thing(id)
+    }
+
     /// Is this entity a rule?
     pub fn is_rule(&self, id: ID) -> bool {
         self.rules.get(&id).is_some() && self.has_flags(id)
@@ -278,6 +391,59 @@ impl World {
         self.synonyms.insert(verb.to_string(), canon.to_string());
     }
 
+    /// Adds a multi-word verb phrase, e.g., "pick up", as a synonym for a verb.  The
+    /// parser replaces the phrase with the verb when it begins a command.
+    pub fn add_phrase(&mut self, canon: &str, phrase: &str) {
+        let words: Vec<String> = phrase.split_whitespace().map(|w| w.to_string()).collect();
+        assert!(words.len() > 1, "verb phrase has only one word: {}", phrase);
+        assert!(
+            !self.phrases.iter().any(|(p, _)| *p == words),
+            "verb phrase already defined: {}",
+            phrase
+        );
+
+        self.phrases.push((words, canon.to_string()));
+
+        // Longer phrases take precedence, e.g., "go in to" over "go in".
+        self.phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+    }
+
+    /// All of the words the game knows: verbs and their synonyms, the words of verb
+    /// phrases and command patterns, pronouns, and the things' nouns and adjectives.
+    pub fn vocabulary(&self) -> BTreeSet<String> {
+        let mut words = self.command_vocabulary();
+        words.extend(self.thing_words(self.things.keys()));
+        words
+    }
+
+    /// The words the game knows that aren't the names of things: verbs and their
+    /// synonyms, the words of verb phrases and command patterns, and pronouns.
+    pub fn command_vocabulary(&self) -> BTreeSet<String> {
+        let mut words: BTreeSet<String> = self.verbs.iter().cloned().collect();
+
+        words.extend(self.phrases.iter().flat_map(|(phrase, _)| phrase.iter().cloned()));
+        words.extend(
+            self.command_handlers
+                .iter()
+                .flat_map(|handler| handler.words().map(|w| w.to_string())),
+        );
+        words.extend(grammar::PRONOUNS.iter().map(|w| w.to_string()));
+        words
+    }
+
+    /// The nouns and adjectives of the given things.
+    pub fn thing_words<'a>(&self, ids: impl IntoIterator<Item = &'a ID>) -> BTreeSet<String> {
+        let mut words = BTreeSet::new();
+
+        for id in ids {
+            let thingc = &self.things[id];
+            words.extend(thingc.nouns.iter().cloned());
+            words.extend(thingc.adjectives.iter().cloned());
+        }
+
+        words
+    }
+
     //--------------------------------------------------------------------------------------------
     // Flags
 
@@ -308,6 +474,35 @@ impl World {
         // Consider adding as_flags() to Entity
         fc.unset(flag);
     }
+
+    //--------------------------------------------------------------------------------------------
+    // Scoring
+
+    /// Gets the named achievement, which must have been declared.
+    pub fn achievement(&self, name: &str) -> &Achievement {
+        self.achievements
+            .iter()
+            .find(|a| a.name == name)
+            .unwrap_or_else(|| panic!("Undeclared achievement: {}", name))
+    }
+
+    /// Has the player earned the named achievement?
+    pub fn has_earned(&self, name: &str) -> bool {
+        self.earned.iter().any(|earned| earned == name)
+    }
+
+    /// Awards the named achievement to the player, if it hasn't already been earned,
+    /// and returns the points awarded.
+    pub fn award(&mut self, name: &str) -> usize {
+        if self.has_earned(name) {
+            return 0;
+        }
+
+        let points = self.achievement(name).points;
+        self.score += points;
+        self.earned.push(name.into());
+        points
+    }
 }
 
 /// WorldQuery: A query interface, for use by scenario hooks
@@ -325,6 +520,19 @@ pub trait WorldQuery {
     // Returns true if the tagged owner owns the tagged thing, and
     // false otherwise
     fn owns(&self, owner: &str, thing: &str) -> bool;
+
+    // Gets the player's score.
+    fn score(&self) -> usize;
+
+    // Returns true if the tagged room is dark, i.e., the player can't see in it.
+    fn is_dark(&self, room: &str) -> bool;
+
+    // Returns the total size of the things the tagged entity holds.
+    fn load_size(&self, owner: &str) -> usize;
+
+    // Returns the total weight of the things the tagged entity holds, including
+    // whatever is in or on them.
+    fn load_weight(&self, owner: &str) -> usize;
 }
 
 impl WorldQuery for World {
@@ -360,4 +568,24 @@ impl WorldQuery for World {
         }
     }
 
+    // Gets the player's score.
+    fn score(&self) -> usize {
+        self.score
+    }
+
+    // Returns true if the tagged room is dark, i.e., the player can't see in it.
+    fn is_dark(&self, room: &str) -> bool {
+        phys::is_dark(self, self.lookup(room))
+    }
+
+    // Returns the total size of the things the tagged entity holds.
+    fn load_size(&self, owner: &str) -> usize {
+        phys::load_size(self, self.lookup(owner))
+    }
+
+    // Returns the total weight of the things the tagged entity holds, including
+    // whatever is in or on them.
+    fn load_weight(&self, owner: &str) -> usize {
+        phys::load_weight(self, self.lookup(owner))
+    }
 }
@@ -13,6 +13,7 @@ use crate::entity::prose_component::*;
 use crate::entity::room_component::*;
 use crate::entity::rule_component::*;
 use crate::entity::thing_component::*;
+use crate::grammar;
 use crate::phys;
 use crate::player_control::CommandHandler;
 use crate::types::*;
@@ -37,6 +38,27 @@ pub enum WBEvent<'a> {
 
     /// The player enters (or tries to enter) the tagged entity
     EnterRoom(&'a str),
+
+    /// The player puts (or tries to put) the first tagged entity in the second
+    PutIn(&'a str, &'a str),
+
+    /// The player puts (or tries to put) the first tagged entity on the second
+    PutOn(&'a str, &'a str),
+
+    /// The player opens (or tries to open) the tagged entity
+    OpenThing(&'a str),
+
+    /// The player closes (or tries to close) the tagged entity
+    CloseThing(&'a str),
+
+    /// The player locks (or tries to lock) the tagged entity
+    LockThing(&'a str),
+
+    /// The player unlocks (or tries to unlock) the tagged entity
+    UnlockThing(&'a str),
+
+    /// The player moves (or tries to move) out of the tagged room while it's dark
+    MoveInDark(&'a str),
 }
 
 /// Expectations, to be checked when world-building is complete.
@@ -53,6 +75,15 @@ enum Is {
 
     /// The entity is a thing.
     Thing(ID),
+
+    /// The entity is a container.
+    Container(ID),
+
+    /// The entity is a supporter.
+    Supporter(ID),
+
+    /// The entity is a door between two rooms.
+    Door(ID),
 }
 
 //-----------------------------------------------------------------------------------------------
@@ -77,6 +108,21 @@ enum Is {
 pub struct WorldBuilder {
     world: World,
     expectations: HashSet<Is>,
+
+    // The number of custom commands added so far; they precede the built-ins.
+    custom_commands: usize,
+
+    // Links back from rooms that might not have been created yet: (from, dir, to).
+    back_links: Vec<(ID, Dir, ID)>,
+
+    // The scenario's synonyms, (canon, word), added when the world is complete.
+    synonyms: Vec<(String, String)>,
+}
+
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WorldBuilder {
@@ -89,6 +135,9 @@ impl WorldBuilder {
         let mut this = Self {
             world: World::new(),
             expectations: HashSet::new(),
+            custom_commands: 0,
+            back_links: Vec::new(),
+            synonyms: Vec::new(),
         };
 
         // NEXT, create LIMBO, the container for things which aren't anywhere else.
@@ -102,7 +151,9 @@ impl WorldBuilder {
         this.world.pid = pid;
 
         this.world.players.insert(pid, PlayerComponent::new());
-        this.world.things.insert(pid, ThingComponent::new("Yourself", "self"));
+        let mut thingc = ThingComponent::new("Yourself", "self");
+        thingc.nouns.extend(vec!["me".into(), "myself".into()]);
+        this.world.things.insert(pid, thingc);
         this.add_inventory(pid);
         this.add_location(pid);
         this.add_flag(pid, Flag::Scenery);
@@ -110,23 +161,60 @@ impl WorldBuilder {
         this
     }
 
+    /// Adds a custom command matching a grammar pattern, e.g., `wash {visible}` or
+    /// `put {held} in {visible}`.  The hook receives the tags of the things and the
+    /// words that fill the pattern's slots, in order.  Custom commands take precedence
+    /// over the built-in commands, and are tried in the order they are added.
+    pub fn command(&mut self, pattern: &str, hook: CommandHook) {
+        let handler = CommandHandler::custom(pattern, hook);
+        self.add_custom_verb(handler.verb());
+        self.world.command_handlers.insert(self.custom_commands, handler);
+        self.custom_commands += 1;
+    }
+
     /// Adds a custom command consisting of a single verb.
     pub fn verb(&mut self, word: &str, hook: CommandHook) {
-        // TODO: Add to list of verbs
-        self.world.command_handlers.push(CommandHandler::verb(word, hook));
+        self.command(word, hook);
     }
 
     /// Adds a custom command triggered by a specific verb and noun.
     pub fn verb_noun(&mut self, verb: &str, noun: &str, hook: CommandHook) {
-        // TODO: Add to list of verbs
-        self.world.command_handlers.push(CommandHandler::verb_noun(verb, noun, hook));
+        self.command(&format!("{} {}", verb, noun), hook);
     }
 
     /// Adds a custom command triggered by a specific verb and a noun representing
     /// a thing that's visible to the player.
     pub fn verb_visible(&mut self, verb: &str, hook: CommandHook) {
-        // TODO: Add to list of verbs
-        self.world.command_handlers.push(CommandHandler::verb_visible(verb, hook));
+        self.command(&format!("{} {{visible}}", verb), hook);
+    }
+
+    /// Adds a synonym for a verb, e.g., "rinse" for "wash".  The verb may be a standard
+    /// verb or one used by a custom command.  The synonym must not already be known.
+    pub fn synonym(&mut self, verb: &str, word: &str) {
+        self.synonyms.push((verb.into(), word.into()));
+    }
+
+    /// Adds a multi-word verb phrase that the parser folds into the given verb,
+    /// e.g., "climb up" for "up".
+    pub fn verb_phrase(&mut self, verb: &str, phrase: &str) {
+        self.world.add_phrase(verb, phrase);
+    }
+
+    /// Declares an achievement for which the player can earn points, e.g.,
+    /// "took the sword".  Scripts award the points with `award()`.
+    pub fn achievement(&mut self, name: &str, points: usize) {
+        assert!(!self.world.achievements.iter().any(|a| a.name == name),
+            "Achievement already declared: {}", name);
+        self.world.achievements.push(Achievement {
+            name: name.into(),
+            points,
+        });
+    }
+
+    /// Disables undo and redo, e.g., for scenarios in which combat or death should
+    /// be permanent.
+    pub fn disable_undo(&mut self) {
+        self.world.allow_undo = false;
     }
 
     /// Configures the player.
@@ -160,6 +248,16 @@ impl WorldBuilder {
             .flag(Flag::Scenery)
     }
 
+    /// Creates or configures a door: an openable feature that connects two rooms,
+    /// each of which links to it with `RoomBuilder::door()`.  It's visible from both
+    /// rooms, and is in the same state on both sides.  It starts out closed.
+    pub fn door(&mut self, tag: &str, name: &str, noun: &str) -> ThingBuilder<'_> {
+        let id = self.world.alloc(tag);
+        self.world.doors.entry(id).or_default();
+
+        self.feature(tag, name, noun).openable()
+    }
+
     /// Creates or configures a thing.
     pub fn thing(&mut self, tag: &str, name: &str, noun: &str) -> ThingBuilder {
         let id = self.world.alloc(tag);
@@ -205,6 +303,23 @@ impl WorldBuilder {
         self.build_event_rule("on", evt, rulec)
     }
 
+    /// Creates and configures a rule that ends the game with a win at the end of any
+    /// turn in which the predicate is met.  Use the RuleBuilder's `print()` to add
+    /// the ending prose.
+    pub fn win_when(&mut self, predicate: RulePredicate) -> RuleBuilder<'_> {
+        let mut rulec = RuleComponent::newx(Event::Turn, predicate);
+        rulec.script.win();
+        self.build_ending_rule("win", rulec)
+    }
+
+    /// Creates and configures a rule that ends the game with a loss at the end of any
+    /// turn in which the predicate is met.  Use the RuleBuilder's `print()` to add
+    /// the ending prose.
+    pub fn lose_when(&mut self, predicate: RulePredicate) -> RuleBuilder<'_> {
+        let mut rulec = RuleComponent::newx(Event::Turn, predicate);
+        rulec.script.lose();
+        self.build_ending_rule("lose", rulec)
+    }
 
     /// Completes world-building, after checking that all expectations are met.
     pub fn world(self) -> World {
@@ -230,9 +345,66 @@ impl WorldBuilder {
                         "Expected thing: [{}] {}",
                         id, self.world.tag(id));
                 }
+                Is::Container(id) => {
+                    assert!(self.world.is_thing(id) && self.world.has_flag(id, Flag::Container),
+                        "Expected container: [{}] {}",
+                        id, self.world.tag(id));
+                }
+                Is::Supporter(id) => {
+                    assert!(self.world.is_thing(id) && self.world.has_flag(id, Flag::Supporter),
+                        "Expected supporter: [{}] {}",
+                        id, self.world.tag(id));
+                }
+                Is::Door(id) => {
+                    assert!(self.world.is_door(id) && self.world.doors[&id].rooms.len() == 2,
+                        "Expected door between two rooms: [{}] {}",
+                        id, self.world.tag(id));
+                }
             }
         }
-        self.world
+
+        // NEXT, add the links back, now that all of the rooms exist.
+        let mut world = self.world;
+
+        for (from, dir, to) in self.back_links {
+            let links = &mut world.rooms.get_mut(&from).unwrap().links;
+
+            match links.get(&dir) {
+                Some(LinkDest::Room(id)) if *id == to => (),
+                Some(_) => panic!(
+                    "Link back conflicts with existing link: [{}] {} {:?}",
+                    from, world.tag(from), dir
+                ),
+                None => {
+                    links.insert(dir, LinkDest::Room(to));
+                }
+            }
+        }
+
+        // NEXT, add the scenario's synonyms, now that all of the custom verbs are known.
+        for (verb, word) in &self.synonyms {
+            assert!(is_canonical(&world, verb), "Synonym {} for unknown verb: {}", word, verb);
+            assert!(!world.verbs.contains(word), "Synonym collides with a known word: {}", word);
+            world.add_syn(verb, word);
+        }
+
+        // NEXT, the parser replaces synonyms, so a command can only match if its
+        // verb and other words are canonical.
+        for handler in &world.command_handlers {
+            for word in handler.words() {
+                if let Some(canon) = world.synonyms.get(word) {
+                    assert!(canon == word,
+                        "Command uses synonym {} rather than {}", word, canon);
+                }
+            }
+
+            assert!(is_canonical(&world, handler.verb()),
+                "Command verb isn't in the vocabulary: {}", handler.verb());
+        }
+
+        // NEXT, compute the maximum score.
+        world.max_score = world.achievements.iter().map(|a| a.points).sum();
+        world
     }
 
     //-------------------------------------------------------------------------------------------
@@ -243,6 +415,15 @@ impl WorldBuilder {
         self.expectations.insert(expectation);
     }
 
+    /// Adds a custom command's verb to the vocabulary, unless it's already known.
+    /// Several commands may share a verb, and a custom command may extend a
+    /// standard verb.
+    fn add_custom_verb(&mut self, verb: &str) {
+        if !self.world.synonyms.contains_key(verb) {
+            self.world.add_verb(verb);
+        }
+    }
+
     /// Adds a location to an entity if it doesn't have one.  The entity will initially
     /// be in LIMBO.
     fn add_location(&mut self, id: ID) {
@@ -265,6 +446,16 @@ impl WorldBuilder {
         phys::put_in(&mut self.world, thing, loc);
     }
 
+    /// Sets the location of the thing to the supporter with the given tag.  The
+    /// thing is on the supporter rather than in it.
+    fn set_location_on(&mut self, thing: ID, supporter_tag: &str) {
+        self.set_location(thing, supporter_tag);
+
+        let sid = self.world.alloc(supporter_tag);
+        phys::put_on(&mut self.world, thing, sid);
+        self.expect(Is::Supporter(sid));
+    }
+
     /// Adds an inventory to an entity if it doesn't have one.
     fn add_inventory(&mut self, id: ID) {
         if self.world.inventories.get(&id).is_none() {
@@ -335,6 +526,52 @@ impl WorldBuilder {
                 self.expect(Is::Room(rid));
                 format!("{}-enter-{}", kind, room_tag)
             }
+            WBEvent::PutIn(thing_tag, container_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                let cid = self.world.alloc(container_tag);
+                rulec.event = Event::PutIn(self.world.pid, tid, cid);
+                self.expect(Is::Thing(tid));
+                self.expect(Is::Container(cid));
+                format!("{}-put-{}-in-{}", kind, thing_tag, container_tag)
+            }
+            WBEvent::PutOn(thing_tag, supporter_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                let sid = self.world.alloc(supporter_tag);
+                rulec.event = Event::PutOn(self.world.pid, tid, sid);
+                self.expect(Is::Thing(tid));
+                self.expect(Is::Supporter(sid));
+                format!("{}-put-{}-on-{}", kind, thing_tag, supporter_tag)
+            }
+            WBEvent::OpenThing(thing_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                rulec.event = Event::OpenThing(self.world.pid, tid);
+                self.expect(Is::Thing(tid));
+                format!("{}-open-{}", kind, thing_tag)
+            }
+            WBEvent::CloseThing(thing_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                rulec.event = Event::CloseThing(self.world.pid, tid);
+                self.expect(Is::Thing(tid));
+                format!("{}-close-{}", kind, thing_tag)
+            }
+            WBEvent::LockThing(thing_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                rulec.event = Event::LockThing(self.world.pid, tid);
+                self.expect(Is::Thing(tid));
+                format!("{}-lock-{}", kind, thing_tag)
+            }
+            WBEvent::UnlockThing(thing_tag) => {
+                let tid = self.world.alloc(thing_tag);
+                rulec.event = Event::UnlockThing(self.world.pid, tid);
+                self.expect(Is::Thing(tid));
+                format!("{}-unlock-{}", kind, thing_tag)
+            }
+            WBEvent::MoveInDark(room_tag) => {
+                let rid = self.world.alloc(room_tag);
+                rulec.event = Event::MoveInDark(self.world.pid, rid);
+                self.expect(Is::Room(rid));
+                format!("{}-move-in-dark-{}", kind, room_tag)
+            }
         };
 
         let id = self.world.alloc(&tag);
@@ -347,6 +584,21 @@ impl WorldBuilder {
             id,
         }
     }
+
+    /// Creates a rule that ends the game.  Ending rules are tagged automatically, as
+    /// a scenario may have any number of them.
+    fn build_ending_rule(&mut self, kind: &str, rulec: RuleComponent) -> RuleBuilder<'_> {
+        let tag = format!("{}-when-{}", kind, self.world.rules.len());
+        let id = self.world.alloc(&tag);
+        self.world.rules.insert(id, rulec);
+        self.add_flag_set(id);
+
+        RuleBuilder {
+            wb: self,
+            tag,
+            id,
+        }
+    }
 }
 
 /// # PlayerBuilder -- A tool for configuring the player entity.
@@ -385,11 +637,30 @@ impl<'a> PlayerBuilder<'a> {
         self.wb.add_flag(self.wb.world.pid, flag);
         self
     }
+
+    /// Limits the total size of the things the player can carry.
+    pub fn max_size(self, max: usize) -> PlayerBuilder<'a> {
+        self.wb.world.inventories.get_mut(&self.wb.world.pid).unwrap().max_size = Some(max);
+        self
+    }
+
+    /// Limits the total weight of the things the player can carry, including
+    /// whatever is in or on them.
+    pub fn max_weight(self, max: usize) -> PlayerBuilder<'a> {
+        self.wb.world.inventories.get_mut(&self.wb.world.pid).unwrap().max_weight = Some(max);
+        self
+    }
+}
+
+/// Is the word a canonical verb, rather than a synonym or an unknown word?
+fn is_canonical(world: &World, verb: &str) -> bool {
+    world.synonyms.get(verb).map(|canon| canon.as_str()) == Some(verb)
 }
 
 /// # RoomBuilder -- A tool for creating and configuring room entities.
 pub struct RoomBuilder<'a> {
     wb: &'a mut WorldBuilder,
+    #[allow(dead_code)] // For error messages, as in RuleBuilder.
     tag: String,
     id: ID,
 }
@@ -427,6 +698,27 @@ impl<'a> RoomBuilder<'a> {
         self
     }
 
+    /// Creates a link from this room to another room given the direction and
+    /// the other room's tag, and a link back from the other room in the opposite
+    /// direction.
+    pub fn link_both(self, dir: Dir, room_tag: &str) -> RoomBuilder<'a> {
+        let dest = self.wb.world.alloc(room_tag);
+        self.wb.back_links.push((dest, dir.opposite(), self.id));
+        self.link(dir, room_tag)
+    }
+
+    /// Creates a link from this room through a door, given the direction and the
+    /// door's tag.  The room on the other side must link to the same door.
+    pub fn door(self, dir: Dir, door_tag: &str) -> RoomBuilder<'a> {
+        let door = self.wb.world.alloc(door_tag);
+        self.wb.expect(Is::Door(door));
+
+        self.wb.world.doors.entry(door).or_default().rooms.insert(self.id);
+        self.wb.world.rooms.get_mut(&self.id).unwrap().links.insert(dir, LinkDest::Door(door));
+
+        self
+    }
+
     /// Adds a dead end in the given direction.
     pub fn dead_end(self, dir: Dir, text: &str) -> RoomBuilder<'a> {
         let dead_end = LinkDest::DeadEnd(text.into());
@@ -438,6 +730,7 @@ impl<'a> RoomBuilder<'a> {
 /// # ThingBuilder -- A tool for creating and configuring thing entities.
 pub struct ThingBuilder<'a> {
     wb: &'a mut WorldBuilder,
+    #[allow(dead_code)] // For error messages, as in RuleBuilder.
     tag: String,
     id: ID,
 }
@@ -448,6 +741,90 @@ impl<'a> ThingBuilder<'a> {
         self.wb.set_location(self.id, loc);
         self
     }
+
+    /// Sets the thing's initial location to be on the supporter with the given tag.
+    pub fn location_on(self, supporter: &str) -> ThingBuilder<'a> {
+        self.wb.set_location_on(self.id, supporter);
+        self
+    }
+
+    /// Makes the thing an agent, a character that `{agent}` slots can name, e.g.,
+    /// `give {held} to {agent}`.  Agents can't be picked up.
+    pub fn agent(self) -> ThingBuilder<'a> {
+        self.wb.add_flag(self.id, Flag::Immovable);
+        self.wb.add_flag(self.id, Flag::Agent);
+        self
+    }
+
+    /// Makes the thing a container: the player can put things in it.
+    pub fn container(self) -> ThingBuilder<'a> {
+        self.wb.add_inventory(self.id);
+        self.wb.add_flag(self.id, Flag::Container);
+        self
+    }
+
+    /// Makes the thing openable.  It starts out closed; set its Open flag to start
+    /// it open.
+    pub fn openable(self) -> ThingBuilder<'a> {
+        self.wb.add_flag(self.id, Flag::Openable);
+        self
+    }
+
+    /// Makes the thing openable and lockable with the key with the given tag.  It
+    /// starts out closed and locked.
+    pub fn lockable(self, key: &str) -> ThingBuilder<'a> {
+        let kid = self.wb.world.alloc(key);
+        self.wb.expect(Is::Thing(kid));
+        self.wb.world.things.get_mut(&self.id).unwrap().key = Some(kid);
+
+        self.wb.add_flag(self.id, Flag::Lockable);
+        self.wb.add_flag(self.id, Flag::Locked);
+        self.openable()
+    }
+
+    /// Sets the thing's size, for filling inventories with limited room.  The
+    /// default is 0.
+    pub fn size(self, size: usize) -> ThingBuilder<'a> {
+        self.wb.world.things.get_mut(&self.id).unwrap().size = size;
+        self
+    }
+
+    /// Sets the thing's weight, not counting whatever is in or on it.  The default
+    /// is 0.
+    pub fn weight(self, weight: usize) -> ThingBuilder<'a> {
+        self.wb.world.things.get_mut(&self.id).unwrap().weight = weight;
+        self
+    }
+
+    /// Limits the total size of the things that can be put in or on the thing.
+    pub fn max_size(self, max: usize) -> ThingBuilder<'a> {
+        self.wb.add_inventory(self.id);
+        self.wb.world.inventories.get_mut(&self.id).unwrap().max_size = Some(max);
+        self
+    }
+
+    /// Limits the total weight of the things that can be put in or on the thing,
+    /// including whatever is in or on them.
+    pub fn max_weight(self, max: usize) -> ThingBuilder<'a> {
+        self.wb.add_inventory(self.id);
+        self.wb.world.inventories.get_mut(&self.id).unwrap().max_weight = Some(max);
+        self
+    }
+
+    /// Makes the thing a light source, which the player can light and extinguish.
+    /// It starts out unlit; set its Lit flag to start it lit.
+    pub fn light_source(self) -> ThingBuilder<'a> {
+        self.wb.add_flag(self.id, Flag::LightSource);
+        self
+    }
+
+    /// Makes the thing a supporter: the player can put things on it.
+    pub fn supporter(self) -> ThingBuilder<'a> {
+        self.wb.add_inventory(self.id);
+        self.wb.add_flag(self.id, Flag::Supporter);
+        self
+    }
+
     /// Adds descriptive prose to the thing.
     pub fn on_examine(self, text: &str) -> ThingBuilder<'a> {
         self.wb.add_prose(self.id, ProseType::Thing, text);
@@ -487,6 +864,31 @@ impl<'a> ThingBuilder<'a> {
         self
     }
 
+    /// Adds nouns by which the player can refer to the thing, in addition to the
+    /// noun it was created with.
+    pub fn nouns(self, nouns: &[&str]) -> ThingBuilder<'a> {
+        let thingc = self.wb.world.things.get_mut(&self.id).unwrap();
+        thingc.nouns.extend(nouns.iter().map(|n| n.to_string()));
+        self
+    }
+
+    /// Adds adjectives the player can use to tell the thing apart from others with
+    /// the same nouns, e.g., "red" and "grey" stones.
+    pub fn adjectives(self, adjectives: &[&str]) -> ThingBuilder<'a> {
+        let thingc = self.wb.world.things.get_mut(&self.id).unwrap();
+        thingc.adjectives.extend(adjectives.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Sets the pronoun by which the player can refer to the thing, e.g., "them" for
+    /// a pair of boots.  The default is "it".
+    pub fn pronoun(self, pronoun: &str) -> ThingBuilder<'a> {
+        assert!(grammar::is_pronoun(pronoun), "Not a pronoun: {}", pronoun);
+        let thingc = self.wb.world.things.get_mut(&self.id).unwrap();
+        thingc.pronoun = pronoun.into();
+        self
+    }
+
     /// Sets a flag on the thing.
     pub fn flag(self, flag: Flag) -> ThingBuilder<'a> {
         self.wb.add_flag(self.id, flag);
@@ -574,6 +976,14 @@ impl<'a> RuleBuilder<'a> {
         self
     }
 
+    /// Declares an achievement, and awards its points when the rule fires.
+    pub fn award(self, name: &str, points: usize) -> RuleBuilder<'a> {
+        self.wb.achievement(name, points);
+        let rulec = &mut self.wb.world.rules.get_mut(&self.id).unwrap();
+        rulec.script.award(name);
+        self
+    }
+
     /// Kills the tagged entity, i.e., sets the Dead flag.
     /// TODO: At present, really presumes that the entity is the player.
     /// Eventually, we might have NPCs, monsters, etc.  But the script
@@ -598,3 +1008,69 @@ impl<'a> RuleBuilder<'a> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synonyms() {
+        let mut wb = WorldBuilder::new();
+        wb.verb("wash", &|_, _, _| Ok(1));
+        wb.synonym("wash", "rinse");
+        wb.synonym("get", "grab");
+        let world = wb.world();
+
+        assert_eq!(world.synonyms["rinse"], "wash");
+        assert_eq!(world.synonyms["grab"], "get");
+    }
+
+    #[test]
+    #[should_panic(expected = "collides")]
+    fn synonym_collision() {
+        let mut wb = WorldBuilder::new();
+        wb.synonym("get", "drop");
+        wb.world();
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown verb")]
+    fn synonym_for_unknown_verb() {
+        let mut wb = WorldBuilder::new();
+        wb.synonym("wash", "rinse");
+        wb.world();
+    }
+
+    #[test]
+    #[should_panic(expected = "synonym take")]
+    fn command_with_synonym() {
+        let mut wb = WorldBuilder::new();
+        wb.command("take {visible}", &|_, _, _| Ok(1));
+        wb.world();
+    }
+
+    #[test]
+    fn doors() {
+        let mut wb = WorldBuilder::new();
+        wb.room("hall", "Hall").door(Dir::North, "door");
+        wb.room("study", "Study").door(Dir::South, "door");
+        wb.door("door", "oak door", "door");
+        let world = wb.world();
+
+        let (hall, study, door) = (world.lookup("hall"), world.lookup("study"), world.lookup("door"));
+        assert!(world.has_flag(door, Flag::Openable));
+        assert!(!world.has_flag(door, Flag::Open));
+        assert_eq!(world.doors[&door].other_side(hall), study);
+        assert_eq!(world.doors[&door].other_side(study), hall);
+        assert!(phys::doors(&world, study).contains(&door));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected door")]
+    fn door_with_one_side() {
+        let mut wb = WorldBuilder::new();
+        wb.room("hall", "Hall").door(Dir::North, "door");
+        wb.door("door", "oak door", "door");
+        wb.world();
+    }
+}